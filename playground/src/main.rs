@@ -1,8 +1,5 @@
-#![feature(universal_impl_trait)]
-#![feature(conservative_impl_trait)]
-
-extern crate stpl;
 extern crate serde;
+extern crate stpl;
 #[macro_use]
 extern crate serde_derive;
 
@@ -12,40 +9,42 @@ use stpl::html;
 pub mod templates;
 use templates::*;
 
-
 pub fn print_template(tpl: impl stpl::Render) {
     let mut v = vec![];
-    tpl
-        .render(&mut stpl::html::Renderer::new(&mut v))
-        .unwrap();
+    tpl.render(&mut v).unwrap();
     std::io::stdout().write_all(&v).unwrap();
 }
 
-pub fn home_tpl() -> impl stpl::Template {
+pub fn home_tpl() -> impl stpl::Template<Data = templates::home::Data> {
     html::Template::new("home", ::templates::home::page)
 }
 
 fn main() {
-    stpl::handle_dynamic()
-        .template(&home_tpl());
+    stpl::handle_dynamic().template(&home_tpl());
 
     let data = templates::home::Data {
         page: base::Data {
             title: "Hello!".into(),
         },
-        name: "William".into()
+        name: "William".into(),
     };
 
-    println!("Change `src/templates/home.rs` and rerun `cargo build` to pick a new template version");
+    let dev = stpl::dev::Watcher::new(env!("CARGO_MANIFEST_DIR"))
+        .watch("src/templates")
+        .start()
+        .unwrap();
+
+    println!("Change `src/templates/home.rs` to pick a new template version");
     println!();
     loop {
         println!("Static:");
         print_template(templates::home::page(&data));
-        println!("");
+        println!();
         println!("dynamic:");
-        std::io::stdout().write_all(&stpl::render_dynamic_self(&home_tpl(), &data).unwrap()).unwrap();
-        println!("");
+        std::io::stdout()
+            .write_all(&dev.render(&home_tpl(), &data).unwrap())
+            .unwrap();
+        println!();
         std::thread::sleep(std::time::Duration::from_secs(5));
     }
-
 }
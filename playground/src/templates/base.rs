@@ -5,14 +5,14 @@ pub struct Data {
     pub title: String,
 }
 
-pub fn base<C : Render+'static>(data: &Data, content : C) -> impl Render  {
+#[rustfmt::skip]
+pub fn base<'a, C : Render + 'a>(data: &'a Data, content : C) -> impl Render + 'a {
     use stpl::html::*;
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    html((
-        head(
-            title(data.title.clone())
+    html.with((
+        head.with(
+            title.with(data.title.as_str())
         ),
-        body(
+        body.with(
             content
         )
     ))
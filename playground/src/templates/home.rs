@@ -8,16 +8,16 @@ pub struct Data {
     pub name: String,
 }
 
-pub fn page(data: &Data) -> impl Render {
+pub fn page<'a>(data: &'a Data) -> impl Render + 'a {
     use stpl::html::*;
-    #[cfg_attr(rustfmt, rustfmt_skip)]
+    #[rustfmt::skip]
     let content = (
-        h1.class("main")("Welcome page"),
-        p(
+        h1.class("main").with("Welcome page"),
+        p.with(
             format!("Hi, {}!", data.name)
         ),
-        ul(
-            (0..2).map(|n| li(n)).collect::<Vec<_>>()
+        ul.with(
+            stpl::each(0..2, |n| li.with(n))
         )
     );
 
@@ -0,0 +1,290 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use html::{body, div, doctype, h1, h2, head, html, iframe, meta, pre, title, RenderExt};
+use {render_dynamic, Error, Template};
+
+/// State shared with the watching thread
+#[derive(Default)]
+struct State {
+    /// Copy of the last successfully built binary
+    binary: Option<PathBuf>,
+    /// Compiler output of the last build, if it failed
+    build_error: Option<String>,
+    /// Number of builds so far, used to name the copies
+    builds: u64,
+}
+
+/// Watches template sources, rebuilding the binary on changes
+///
+/// ```ignore
+/// let dev = dev::Watcher::new(".")
+///     .watch("src/templates")
+///     .cargo_arg("--bin=app")
+///     .start()?;
+///
+/// let page = dev.render(&home_tpl(), &data)?;
+/// ```
+///
+/// Every time a file under the watched paths changes, `cargo build`
+/// is run in the crate directory, and subsequent `render` calls use the
+/// new binary with `render_dynamic`. Until the first rebuild the
+/// statically linked template is used.
+///
+/// If the build (or rendering with the new binary) fails, `render`
+/// returns an HTML error page with the compiler error, and the
+/// statically linked version of the page below it.
+pub struct Watcher {
+    crate_dir: PathBuf,
+    watch: Vec<PathBuf>,
+    cargo_args: Vec<String>,
+    binary: Option<PathBuf>,
+    interval: Duration,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Watcher for the crate in `crate_dir`
+    pub fn new<P: Into<PathBuf>>(crate_dir: P) -> Self {
+        Watcher {
+            crate_dir: crate_dir.into(),
+            watch: vec![],
+            cargo_args: vec![],
+            binary: None,
+            interval: Duration::from_millis(500),
+            state: Arc::default(),
+            stop: Arc::default(),
+            thread: None,
+        }
+    }
+
+    /// Watch files under `path` (relative to the crate directory)
+    ///
+    /// By default the `src` directory is watched.
+    pub fn watch<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.watch.push(path.into());
+        self
+    }
+
+    /// Pass an extra argument to `cargo build`, eg. `--release`
+    pub fn cargo_arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.cargo_args.push(arg.into());
+        self
+    }
+
+    /// Path of the binary built by `cargo build`
+    ///
+    /// The current executable by default.
+    pub fn binary<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.binary = Some(path.into());
+        self
+    }
+
+    /// How often to check the files for changes
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Start watching in a background thread
+    ///
+    /// Watching stops when the `Watcher` is dropped, which waits for
+    /// a build in progress to finish.
+    pub fn start(mut self) -> io::Result<Self> {
+        if self.watch.is_empty() {
+            self.watch.push("src".into());
+        }
+        let binary = match self.binary {
+            Some(ref binary) => self.crate_dir.join(binary),
+            None => env::current_exe()?,
+        };
+        let watch: Vec<_> = self.watch.iter().map(|p| self.crate_dir.join(p)).collect();
+        let build = Build {
+            crate_dir: self.crate_dir.clone(),
+            cargo_args: self.cargo_args.clone(),
+            binary,
+        };
+        let interval = self.interval;
+        let state = self.state.clone();
+        let stop = self.stop.clone();
+        let thread = thread::Builder::new()
+            .name("stpl-dev".into())
+            .spawn(move || {
+                let mut mtimes = snapshot(&watch);
+                loop {
+                    thread::sleep(interval);
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let new_mtimes = snapshot(&watch);
+                    if new_mtimes != mtimes {
+                        mtimes = new_mtimes;
+                        build.run(&state);
+                    }
+                }
+            })?;
+        self.thread = Some(thread);
+        Ok(self)
+    }
+
+    /// Render `template` with `data`, with the most recent version
+    /// of the template
+    pub fn render<T: Template>(&self, template: &T, data: &T::Data) -> Result<Vec<u8>, Error> {
+        let (binary, build_error) = {
+            let state = self.state.lock().expect("stpl-dev state poisoned");
+            (state.binary.clone(), state.build_error.clone())
+        };
+        if let Some(build_error) = build_error {
+            return error_page("Template build failed", &build_error, template, data);
+        }
+        let binary = match binary {
+            Some(binary) => binary,
+            None => return render_static(template, data),
+        };
+        match render_dynamic(&binary, template, data) {
+            Ok(out) => Ok(out),
+            Err(e) => error_page("Template rendering failed", &e.to_string(), template, data),
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        // The thread is gone, so no build can store a new copy anymore
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(binary) = state.binary.take() {
+            let _ = fs::remove_file(binary);
+        }
+    }
+}
+
+/// How to rebuild the binary
+struct Build {
+    crate_dir: PathBuf,
+    cargo_args: Vec<String>,
+    binary: PathBuf,
+}
+
+impl Build {
+    fn run(&self, state: &Mutex<State>) {
+        let result = self.build().and_then(|()| {
+            let builds = state.lock().expect("stpl-dev state poisoned").builds + 1;
+            self.copy_binary(builds).map(|copy| (copy, builds))
+        });
+        let mut state = state.lock().expect("stpl-dev state poisoned");
+        match result {
+            Ok((copy, builds)) => {
+                if let Some(old) = state.binary.replace(copy) {
+                    let _ = fs::remove_file(old);
+                }
+                state.build_error = None;
+                state.builds = builds;
+            }
+            Err(e) => state.build_error = Some(e),
+        }
+    }
+
+    /// Run `cargo build`, returning the compiler output if it fails
+    fn build(&self) -> Result<(), String> {
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let output = Command::new(cargo)
+            .arg("build")
+            .args(&self.cargo_args)
+            .current_dir(&self.crate_dir)
+            .env("CARGO_TERM_COLOR", "never")
+            .output()
+            .map_err(|e| format!("running `cargo build` failed: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).into_owned())
+        }
+    }
+
+    /// Copy the built binary, so the next build can replace it while
+    /// the copy is still rendering
+    fn copy_binary(&self, builds: u64) -> Result<PathBuf, String> {
+        let mut name = format!("stpl-dev-{}-{}", std::process::id(), builds);
+        if let Some(ext) = self.binary.extension() {
+            name.push('.');
+            name.push_str(&ext.to_string_lossy());
+        }
+        let copy = env::temp_dir().join(name);
+        fs::copy(&self.binary, &copy)
+            .map_err(|e| format!("copying `{}` failed: {}", self.binary.display(), e))?;
+        Ok(copy)
+    }
+}
+
+/// Modification times of all the files under `paths`
+fn snapshot(paths: &[PathBuf]) -> Vec<(PathBuf, SystemTime)> {
+    fn visit(path: &Path, out: &mut Vec<(PathBuf, SystemTime)>) {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return,
+        };
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    visit(&entry.path(), out);
+                }
+            }
+        } else if let Ok(mtime) = metadata.modified() {
+            out.push((path.to_owned(), mtime));
+        }
+    }
+
+    let mut out = vec![];
+    for path in paths {
+        visit(path, &mut out);
+    }
+    out.sort();
+    out
+}
+
+fn render_static<T: Template>(template: &T, data: &T::Data) -> Result<Vec<u8>, Error> {
+    let mut out = vec![];
+    template.render(data, &mut out)?;
+    Ok(out)
+}
+
+/// Page with `message`, and the statically linked version of
+/// the page below it
+fn error_page<T: Template>(
+    heading: &str,
+    message: &str,
+    template: &T,
+    data: &T::Data,
+) -> Result<Vec<u8>, Error> {
+    let fallback = String::from_utf8_lossy(&render_static(template, data)?).into_owned();
+    let page = (
+        doctype("html"),
+        html.with((
+            head.with((meta.charset("utf-8"), title.with(heading))),
+            body.style("margin: 0; font-family: sans-serif").with((
+                div.style("padding: 1em; background: #fdd; color: #600")
+                    .with((h1.with(heading), pre.style("overflow: auto").with(message))),
+                h2.style("padding: 0 1em").with("Statically linked version"),
+                iframe
+                    .srcdoc(fallback)
+                    .title("Statically linked version")
+                    .style("width: 100%; height: 80vh; border: none")
+                    .with(()),
+            )),
+        )),
+    );
+    page.try_render_to_vec()
+}
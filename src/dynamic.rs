@@ -0,0 +1,177 @@
+use std::env;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use transport::{self, Bincode, BuiltIn, Codec, Decoder};
+use {Error, Renderer};
+
+/// Environment variable with the name of the template the binary
+/// is asked to render
+const TEMPLATE_ENV: &str = "STPL_DYNAMIC_TEMPLATE";
+
+/// Template that can be rendered by a (rebuilt) binary
+///
+/// See `html::Template` for templates made of functions
+/// returning `impl Render`.
+pub trait Template {
+    /// Data the template is rendered with, passed to the binary
+    /// serialized
+    type Data: Serialize + DeserializeOwned;
+
+    /// Name identifying the template
+    fn name(&self) -> &str;
+
+    fn render(&self, data: &Self::Data, r: &mut dyn Renderer) -> io::Result<()>;
+}
+
+/// Render `template` with `data` by running the binary at `path`
+///
+/// The binary must call `handle_dynamic` with the template at the very
+/// beginning of `main`. Since templates are compiled into the binary,
+/// rebuilding it picks up any changes without restarting the caller.
+///
+/// The data is encoded with `transport::Bincode`.
+pub fn render_dynamic<T: Template>(
+    path: &Path,
+    template: &T,
+    data: &T::Data,
+) -> Result<Vec<u8>, Error> {
+    render_dynamic_with(&Bincode, path, template, data)
+}
+
+/// Render `template` with `data` encoded with `codec`, by running
+/// the binary at `path`
+///
+/// Useful with `transport::Json` when debugging. Other codecs have to be
+/// passed to `HandleDynamic::codec` in the binary too.
+pub fn render_dynamic_with<C: Codec, T: Template>(
+    codec: &C,
+    path: &Path,
+    template: &T,
+    data: &T::Data,
+) -> Result<Vec<u8>, Error> {
+    if env::var_os(TEMPLATE_ENV).is_some() {
+        // We are the child already; the template wasn't handled
+        return Err(Error::Dynamic(format!(
+            "nested dynamic rendering of template `{}`",
+            template.name()
+        )));
+    }
+    let data = transport::encode(codec, data)?;
+    let mut child = Command::new(path)
+        .env(TEMPLATE_ENV, template.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // The child reads all the data before writing anything,
+    // so this can't deadlock
+    let written = child.stdin.take().expect("stdin is piped").write_all(&data);
+    // If the child exited early, writing fails with a broken pipe;
+    // its stderr tells why
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Dynamic(format!(
+            "rendering template `{}` failed ({}): {}",
+            template.name(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    written?;
+    Ok(output.stdout)
+}
+
+/// Render `template` with `data` by running the current binary again
+///
+/// See `render_dynamic`.
+pub fn render_dynamic_self<T: Template>(template: &T, data: &T::Data) -> Result<Vec<u8>, Error> {
+    render_dynamic(&env::current_exe()?, template, data)
+}
+
+/// Handle a request for dynamic rendering, if the binary was started
+/// with one
+///
+/// ```ignore
+/// fn main() {
+///     stpl::handle_dynamic()
+///         .template(&home_tpl())
+///         .template(&about_tpl());
+///     // ...
+/// }
+/// ```
+///
+/// When the requested template is found, it's rendered to stdout
+/// and the process exits. If none of the templates matches, the process
+/// exits with an error once the returned value is dropped.
+pub fn handle_dynamic() -> HandleDynamic {
+    HandleDynamic {
+        name: env::var(TEMPLATE_ENV).ok(),
+        decoder: BuiltIn,
+    }
+}
+
+/// See `handle_dynamic`
+pub struct HandleDynamic<D: Decoder = BuiltIn> {
+    name: Option<String>,
+    decoder: D,
+}
+
+impl<D: Decoder> HandleDynamic<D> {
+    /// Decode the data with `codec`
+    ///
+    /// Data encoded with the built-in codecs is decoded by default;
+    /// a custom codec passed to `render_dynamic_with` has to be
+    /// passed here too, before any `template`.
+    pub fn codec<C: Codec>(mut self, codec: C) -> HandleDynamic<C> {
+        HandleDynamic {
+            name: self.name.take(),
+            decoder: codec,
+        }
+    }
+
+    /// Render `template` and exit, if it's the one requested
+    pub fn template<T: Template>(self, template: &T) -> Self {
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name == template.name())
+        {
+            match serve(template, &self.decoder) {
+                Ok(()) => process::exit(0),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1)
+                }
+            }
+        }
+        self
+    }
+}
+
+impl<D: Decoder> Drop for HandleDynamic<D> {
+    fn drop(&mut self) {
+        if let Some(ref name) = self.name {
+            eprintln!("unknown template `{}`", name);
+            process::exit(1);
+        }
+    }
+}
+
+/// Render `template` with data from stdin to stdout
+fn serve<T: Template, D: Decoder>(template: &T, decoder: &D) -> Result<(), Error> {
+    let mut data = vec![];
+    io::stdin().read_to_end(&mut data)?;
+    let data: T::Data = decoder.decode_message(&data)?;
+    let mut out = vec![];
+    template.render(&data, &mut out)?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    stdout.write_all(&out)?;
+    stdout.flush()?;
+    Ok(())
+}
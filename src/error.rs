@@ -0,0 +1,131 @@
+use std::{error, fmt, io};
+
+use html::InvalidAttrName;
+
+/// Template rendering error
+///
+/// `Render::render` returns `io::Result`, so this error travels
+/// wrapped in `io::Error`. Converting the `io::Error` back with
+/// `Error::from` (or `?`) recovers it.
+#[derive(Debug)]
+pub enum Error {
+    /// Writing the output failed
+    Io(io::Error),
+    /// Formatting a value failed
+    Fmt(fmt::Error),
+    /// Tag with an attribute name that is not valid
+    InvalidAttribute(InvalidAttrName),
+    /// Template-level error, eg. missing or invalid data
+    Custom(Box<dyn error::Error + Send + Sync>),
+    /// Serializing or deserializing template data failed
+    Data(Box<dyn error::Error + Send + Sync>),
+    /// Dynamic rendering by another process failed
+    Dynamic(String),
+    /// No template with this name is registered
+    UnknownTemplate(String),
+    /// Template was given data of a different type than it expects
+    WrongDataType {
+        template: String,
+        expected: &'static str,
+    },
+    /// Template data passed to another binary is malformed, or was
+    /// encoded in an incompatible format
+    Transport(String),
+    /// Template data changed shape between the binary that encoded it
+    /// and the one that decoded it
+    DataShape { data_type: &'static str },
+}
+
+impl Error {
+    /// Create a template-level error
+    ///
+    /// ```ignore
+    /// TryFn(move |r: &mut dyn Renderer| match user {
+    ///     Some(ref user) => Ok(user.name.render(r)?),
+    ///     None => Err(Error::custom("no user")),
+    /// })
+    /// ```
+    pub fn custom<E: Into<Box<dyn error::Error + Send + Sync>>>(e: E) -> Self {
+        Error::Custom(e.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "io error: {}", e),
+            Error::Fmt(ref e) => write!(f, "formatting error: {}", e),
+            Error::InvalidAttribute(ref e) => e.fmt(f),
+            Error::Custom(ref e) => e.fmt(f),
+            Error::Data(ref e) => write!(f, "template data error: {}", e),
+            Error::Dynamic(ref e) => e.fmt(f),
+            Error::UnknownTemplate(ref name) => write!(f, "unknown template `{}`", name),
+            Error::WrongDataType {
+                ref template,
+                expected,
+            } => write!(
+                f,
+                "wrong data type for template `{}`, expected `{}`",
+                template, expected
+            ),
+            Error::Transport(ref e) => write!(f, "template data transport error: {}", e),
+            Error::DataShape { data_type } => write!(
+                f,
+                "template data `{}` changed shape, rebuild both binaries",
+                data_type
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Fmt(ref e) => Some(e),
+            Error::InvalidAttribute(ref e) => Some(e),
+            Error::Custom(ref e) | Error::Data(ref e) => Some(&**e),
+            Error::Dynamic(_)
+            | Error::UnknownTemplate(_)
+            | Error::WrongDataType { .. }
+            | Error::Transport(_)
+            | Error::DataShape { .. } => None,
+        }
+    }
+}
+
+/// Unwraps `Error` carried by the `io::Error`, if any
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = e
+                .into_inner()
+                .and_then(|inner| inner.downcast::<Error>().ok());
+            return *inner.expect("checked above");
+        }
+        Error::Io(e)
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(e: fmt::Error) -> Self {
+        Error::Fmt(e)
+    }
+}
+
+impl From<InvalidAttrName> for Error {
+    fn from(e: InvalidAttrName) -> Self {
+        Error::InvalidAttribute(e)
+    }
+}
+
+/// Wraps the `Error` so it can be returned from `Render::render`
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            Error::InvalidAttribute(_) => io::Error::new(io::ErrorKind::InvalidInput, e),
+            e => io::Error::other(e),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::{slice, str};
+
+use transport::Decoder;
+use {Error, Template};
+
+/// Version of the interface between `Library` and `export_templates!`
+///
+/// Bumped on every incompatible change; libraries built with
+/// a different version are refused by `Library::load`.
+pub const ABI_VERSION: u32 = 2;
+
+/// Bytes passed from the library to the host
+///
+/// Allocated by the library, so it must be freed by it too.
+#[repr(C)]
+pub struct Buffer {
+    ptr: *mut u8,
+    len: usize,
+    cap: usize,
+}
+
+impl From<Vec<u8>> for Buffer {
+    fn from(v: Vec<u8>) -> Self {
+        let mut v = std::mem::ManuallyDrop::new(v);
+        Buffer {
+            ptr: v.as_mut_ptr(),
+            len: v.len(),
+            cap: v.capacity(),
+        }
+    }
+}
+
+/// Call `f` with the template name and data passed to `stpl_render`,
+/// storing its output or error message in `out`
+///
+/// # Safety
+///
+/// `name` and `data` must point to `name_len` and `data_len` readable
+/// bytes, and `out` to writable memory for a `Buffer`, which must be
+/// freed with `export_free`.
+#[doc(hidden)]
+pub unsafe fn export_render<F>(
+    name: *const u8,
+    name_len: usize,
+    data: *const u8,
+    data_len: usize,
+    out: *mut Buffer,
+    f: F,
+) -> i32
+where
+    F: FnOnce(&str, &[u8], &mut Vec<u8>) -> Result<(), Error>,
+{
+    let name = slice::from_raw_parts(name, name_len);
+    let data = slice::from_raw_parts(data, data_len);
+    let mut buf = vec![];
+    // Unwinding across the FFI boundary is undefined behavior
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match str::from_utf8(name) {
+        Ok(name) => f(name, data, &mut buf),
+        Err(e) => Err(Error::custom(e)),
+    }));
+    let (code, bytes) = match result {
+        Ok(Ok(())) => (0, buf),
+        Ok(Err(e)) => (1, e.to_string().into_bytes()),
+        Err(_) => (2, b"template panicked".to_vec()),
+    };
+    out.write(Buffer::from(bytes));
+    code
+}
+
+/// Render `template` if it's the one called `name`
+#[doc(hidden)]
+pub fn export_template<T: Template, D: Decoder>(
+    template: &T,
+    decoder: &D,
+    name: &str,
+    data: &[u8],
+    out: &mut Vec<u8>,
+) -> Option<Result<(), Error>> {
+    if template.name() != name {
+        return None;
+    }
+    let data: T::Data = match decoder.decode_message(data) {
+        Ok(data) => data,
+        Err(e) => return Some(Err(e)),
+    };
+    Some(template.render(&data, out).map_err(Error::from))
+}
+
+/// Free a `Buffer` returned by `export_render`
+///
+/// # Safety
+///
+/// `buf` must come from `export_render` in the same library, and
+/// must not be used afterwards.
+#[doc(hidden)]
+pub unsafe fn export_free(buf: Buffer) {
+    drop(Vec::from_raw_parts(buf.ptr, buf.len, buf.cap));
+}
+
+/// Export templates from a `cdylib`, for `Library` to load
+///
+/// ```ignore
+/// // In the template crate, built with `crate-type = ["cdylib"]`
+/// stpl::export_templates!(home_tpl(), about_tpl());
+/// ```
+///
+/// Each argument is an expression evaluating to a `Template`. Data
+/// encoded with the built-in codecs is decoded by default; a custom
+/// codec passed to `Library::render_with` has to be given first:
+///
+/// ```ignore
+/// stpl::export_templates!(codec = MyCodec; home_tpl(), about_tpl());
+/// ```
+#[macro_export]
+macro_rules! export_templates {
+    (codec = $codec:expr; $($template:expr),* $(,)*) => {
+        #[no_mangle]
+        pub extern "C" fn stpl_abi_version() -> u32 {
+            $crate::hot::ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn stpl_render(
+            name: *const u8,
+            name_len: usize,
+            data: *const u8,
+            data_len: usize,
+            out: *mut $crate::hot::Buffer,
+        ) -> i32 {
+            unsafe {
+                $crate::hot::export_render(name, name_len, data, data_len, out, |name, data, buf| {
+                    let decoder = $codec;
+                    $(
+                        if let Some(result) = $crate::hot::export_template(&$template, &decoder, name, data, buf) {
+                            return result;
+                        }
+                    )*
+                    Err($crate::Error::UnknownTemplate(name.to_owned()))
+                })
+            }
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn stpl_free(buf: $crate::hot::Buffer) {
+            unsafe { $crate::hot::export_free(buf) }
+        }
+    };
+    ($($template:expr),* $(,)*) => {
+        $crate::export_templates!(codec = $crate::transport::BuiltIn; $($template),*);
+    };
+}
+
+#[cfg(feature = "hot-reload")]
+pub use self::library::Library;
+
+#[cfg(feature = "hot-reload")]
+mod library {
+    use std::env;
+    use std::fmt;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::slice;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use libloading;
+
+    use super::{Buffer, ABI_VERSION};
+    use transport::{self, Bincode, Codec};
+    use {Error, Template};
+
+    type AbiVersionFn = unsafe extern "C" fn() -> u32;
+    type RenderFn = unsafe extern "C" fn(*const u8, usize, *const u8, usize, *mut Buffer) -> i32;
+    type FreeFn = unsafe extern "C" fn(Buffer);
+
+    /// Number of libraries loaded so far, used to name the copies
+    static LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Loaded copy of the library
+    struct Loaded {
+        lib: Option<libloading::Library>,
+        copy: PathBuf,
+        render: RenderFn,
+        free: FreeFn,
+    }
+
+    impl Loaded {
+        /// See `Library::load` for safety
+        unsafe fn load(path: &Path) -> Result<Loaded, Error> {
+            let err = |e: &dyn fmt::Display| {
+                Error::Dynamic(format!("loading `{}` failed: {}", path.display(), e))
+            };
+            // Load a copy, so the library can be rebuilt while loaded
+            // and a reload is not served from the loader's cache
+            let mut name = format!(
+                "stpl-hot-{}-{}",
+                process::id(),
+                LOADS.fetch_add(1, Ordering::Relaxed)
+            );
+            if let Some(ext) = path.extension() {
+                name.push('.');
+                name.push_str(&ext.to_string_lossy());
+            }
+            let copy = env::temp_dir().join(name);
+            fs::copy(path, &copy).map_err(|e| err(&e))?;
+            match open(&copy) {
+                Ok((lib, render, free)) => Ok(Loaded {
+                    lib: Some(lib),
+                    copy,
+                    render,
+                    free,
+                }),
+                Err(e) => {
+                    let _ = fs::remove_file(&copy);
+                    Err(err(&e))
+                }
+            }
+        }
+    }
+
+    impl Drop for Loaded {
+        fn drop(&mut self) {
+            // Function pointers are not used past this point, and no
+            // data borrowed from the library outlives a `render` call
+            drop(self.lib.take());
+            let _ = fs::remove_file(&self.copy);
+        }
+    }
+
+    /// Load the library at `path` and look up its functions
+    ///
+    /// # Safety
+    ///
+    /// The library's initialization code is run, so it must be a library
+    /// built with `export_templates!`, which doesn't do anything unusual.
+    unsafe fn open(path: &Path) -> Result<(libloading::Library, RenderFn, FreeFn), String> {
+        let lib = libloading::Library::new(path).map_err(|e| e.to_string())?;
+        let version = *lib
+            .get::<AbiVersionFn>(b"stpl_abi_version\0")
+            .map_err(|e| e.to_string())?;
+        let version = version();
+        if version != ABI_VERSION {
+            return Err(format!(
+                "ABI version {} does not match {}",
+                version, ABI_VERSION
+            ));
+        }
+        let render = *lib
+            .get::<RenderFn>(b"stpl_render\0")
+            .map_err(|e| e.to_string())?;
+        let free = *lib
+            .get::<FreeFn>(b"stpl_free\0")
+            .map_err(|e| e.to_string())?;
+        Ok((lib, render, free))
+    }
+
+    /// Templates loaded from a `cdylib` built with `export_templates!`
+    ///
+    /// ```ignore
+    /// // Safety: built from our own template crate with `export_templates!`
+    /// let mut lib = unsafe { hot::Library::load("target/debug/libtemplates.so")? };
+    /// let page = lib.render(&home_tpl(), &data)?;
+    /// // ...after rebuilding the library
+    /// unsafe { lib.reload()? };
+    /// ```
+    ///
+    /// Rendering is a plain function call with serialized data, so it's
+    /// much faster than `render_dynamic` starting a process. `reload`
+    /// needs `&mut self`, so the library can't be unloaded while
+    /// rendering; share it between threads with a `RwLock`.
+    pub struct Library {
+        path: PathBuf,
+        loaded: Loaded,
+    }
+
+    impl Library {
+        /// Load the library at `path`
+        ///
+        /// Fails if the library was built with a different `ABI_VERSION`.
+        ///
+        /// # Safety
+        ///
+        /// The library's initialization code is run, and its exported
+        /// functions are called with no way to check their signatures.
+        /// It must be a library built with `export_templates!` from this
+        /// version of `stpl`, and must not do anything unsound when loaded.
+        pub unsafe fn load<P: Into<PathBuf>>(path: P) -> Result<Library, Error> {
+            let path = path.into();
+            let loaded = Loaded::load(&path)?;
+            Ok(Library { path, loaded })
+        }
+
+        /// Load the library again, eg. after it was rebuilt
+        ///
+        /// If loading fails, the previous version stays loaded.
+        ///
+        /// # Safety
+        ///
+        /// Same as for `load`: the file at the library's path must still
+        /// be a library built with `export_templates!`.
+        pub unsafe fn reload(&mut self) -> Result<(), Error> {
+            self.loaded = Loaded::load(&self.path)?;
+            Ok(())
+        }
+
+        /// Render `template` with `data`, using the template of
+        /// the same name from the library
+        ///
+        /// The data is encoded with `transport::Bincode`.
+        pub fn render<T: Template>(&self, template: &T, data: &T::Data) -> Result<Vec<u8>, Error> {
+            self.render_with(&Bincode, template, data)
+        }
+
+        /// Render `template` with `data` encoded with `codec`
+        ///
+        /// See `render`. Codecs other than the built-in ones have to be
+        /// given to `export_templates!` in the library too.
+        pub fn render_with<C: Codec, T: Template>(
+            &self,
+            codec: &C,
+            template: &T,
+            data: &T::Data,
+        ) -> Result<Vec<u8>, Error> {
+            let data = transport::encode(codec, data)?;
+            let name = template.name();
+            let mut buf = Buffer {
+                ptr: ::std::ptr::null_mut(),
+                len: 0,
+                cap: 0,
+            };
+            let (code, out) = unsafe {
+                let code = (self.loaded.render)(
+                    name.as_ptr(),
+                    name.len(),
+                    data.as_ptr(),
+                    data.len(),
+                    &mut buf,
+                );
+                let out = slice::from_raw_parts(buf.ptr, buf.len).to_vec();
+                (self.loaded.free)(buf);
+                (code, out)
+            };
+            if code == 0 {
+                Ok(out)
+            } else {
+                Err(Error::Dynamic(format!(
+                    "rendering template `{}` failed: {}",
+                    name,
+                    String::from_utf8_lossy(&out)
+                )))
+            }
+        }
+    }
+}
@@ -1,259 +1,101 @@
-use std::borrow::Cow;
-use std::fmt;
 use std::io;
 
 use super::Fn;
-use Render;
+use {Context, ContextRenderer, Error, Render};
+
+mod attrs;
+mod escape;
+mod style;
+mod tag;
+mod template;
+mod values;
+
+pub use self::escape::js_string;
+pub use self::style::{css_string, InvalidStyle, Style};
+pub use self::tag::{
+    is_valid_attr_name, AttrValue, BareTag, DataAttrs, Element, FinalTag, IntoAttrValue,
+    InvalidAttrName, Kind, NonVoid, PreEscaped, Tag,
+};
+pub use self::template::{Template, TemplateFn};
+pub use self::values::{
+    ButtonType, CrossOrigin, Decoding, Dir, Enctype, FetchPriority, InputType, Loading, Method,
+    Preload, ReferrerPolicy, Scope, TrackKind, Wrap,
+};
 
 pub trait RenderExt: Render {
+    /// Render to bytes
+    ///
+    /// Panics if rendering fails, see `try_render_to_vec`.
     fn render_to_vec(&self) -> Vec<u8> {
-        let mut v: Vec<u8> = vec![];
-        self.render(&mut v).unwrap();
-        v
+        self.try_render_to_vec().unwrap()
     }
 
+    /// Render to a string
+    ///
+    /// Panics if rendering fails, see `try_render_to_string`.
     fn render_to_string(&self) -> String {
-        String::from_utf8_lossy(&self.render_to_vec()).into()
+        self.try_render_to_string().unwrap()
     }
-}
 
-impl<T: Render + ?Sized> RenderExt for T {}
-
-impl<T: io::Write> super::Renderer for T {
-    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
-        self.write_all(data)
-    }
-
-    fn write_raw_fmt(&mut self, fmt: &fmt::Arguments) -> io::Result<()> {
-        self.write_fmt(*fmt)
-    }
-
-    fn write(&mut self, data: &[u8]) -> io::Result<()> {
-        for c in data {
-            match *c as char {
-                '&' => self.write_all(b"&amp;")?,
-                '<' => self.write_all(b"&lt;")?,
-                '>' => self.write_all(b"&gt;")?,
-                '"' => self.write_all(b"&quot;")?,
-                '\'' => self.write_all(b"&#x27;")?,
-                '/' => self.write_all(b"&#x2F;")?,
-                // Additional one for old IE (unpatched IE8 and below)
-                // See https://github.com/OWASP/owasp-java-encoder/wiki/Grave-Accent-Issue
-                '`' => self.write_all(b"&#96;")?,
-                _ => self.write_all(&[*c])?,
-            }
-        }
-
-        Ok(())
+    fn try_render_to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut v: Vec<u8> = vec![];
+        self.render(&mut v)?;
+        Ok(v)
     }
-}
-
-type CowStr = Cow<'static, str>;
-
-pub struct BareTag {
-    tag: &'static str,
-}
-
-pub struct Tag {
-    tag: CowStr,
-    attrs: Vec<(CowStr, Option<CowStr>)>,
-}
-
-pub struct FinalTag<I> {
-    tag: CowStr,
-    attrs: Vec<(CowStr, Option<CowStr>)>,
-    inn: I,
-}
 
-impl Render for Tag {
-    fn render(&self, r: &mut super::Renderer) -> io::Result<()> {
-        r.write_raw_str("<")?;
-        r.write_raw_str(&*self.tag)?;
-        for &(ref k, ref v) in self.attrs.iter() {
-            r.write_raw_str(" ")?;
-            r.write_raw_str(&*k)?;
-            if let Some(ref v) = *v {
-                r.write_raw_str("=\"")?;
-                r.write_raw_str(&*v)?;
-                r.write_raw_str("\"")?;
-            }
-        }
-
-        r.write_raw_str(">")?;
-        r.write_raw_str("</")?;
-        r.write_raw_str(&*self.tag)?;
-        r.write_raw_str(">")
+    fn try_render_to_string(&self) -> Result<String, Error> {
+        Ok(String::from_utf8_lossy(&self.try_render_to_vec()?).into())
     }
 }
 
-impl Render for BareTag {
-    fn render(&self, r: &mut super::Renderer) -> io::Result<()> {
-        r.write_raw_str("<")?;
-        r.write_raw_str(&*self.tag)?;
-        r.write_raw_str(">")?;
-        r.write_raw_str("</")?;
-        r.write_raw_str(&*self.tag)?;
-        r.write_raw_str(">")
-    }
-}
+impl<T: Render + ?Sized> RenderExt for T {}
 
-impl<I: Render> Render for FinalTag<I> {
-    fn render(&self, r: &mut super::Renderer) -> io::Result<()> {
-        r.write_raw_str("<")?;
-        r.write_raw_str(&*self.tag)?;
-        for &(ref k, ref v) in self.attrs.iter() {
-            r.write_raw_str(" ")?;
-            r.write_raw_str(&*k)?;
-            if let Some(ref v) = *v {
-                r.write_raw_str("=\"")?;
-                r.write_raw_str(&*v)?;
-                r.write_raw_str("\"")?;
-            }
+/// Implement element marker types and constants for simple tags
+///
+/// Takes the whole element table at once, so marker types
+/// can be put in their own `el` module. Elements whose name would
+/// clash with common variable names get a trailing `_`, with the
+/// tag name given explicitly: `data_ = "data" => Data: Normal`.
+///
+/// Under `use stpl::html::*`, a `let` or closure binding with the same
+/// name as an element constant is a constant pattern, so eg.
+/// `each(items, |s, pos| ..)` fails with a confusing type mismatch.
+/// The rule is that new elements named like a single letter or a word
+/// commonly used for bindings (`s`, `q`, `data`, `time`, `option`,
+/// `style`, `template`...) get the `_`. The elements stpl always had
+/// (`a`, `b`, `i`, `p`, `u`, `title`...) keep their names.
+macro_rules! impl_tags {
+    (@name $t:ident) => { stringify!($t) };
+    (@name $t:ident $n:literal) => { $n };
+    ($($t:ident $(= $n:literal)? => $e:ident: $kind:ident,)*) => {
+        /// Element marker types
+        pub mod el {
+            use super::{Element, Kind};
+            $(
+                pub enum $e {}
+
+                impl Element for $e {
+                    const NAME: &'static str = impl_tags!(@name $t $($n)?);
+                    const KIND: Kind = Kind::$kind;
+                }
+            )*
         }
 
-        r.write_raw_str(">")?;
-        self.inn.render(r)?;
-        r.write_raw_str("</")?;
-        r.write_raw_str(&*self.tag)?;
-        r.write_raw_str(">")
-    }
-}
-
-macro_rules! impl_attr {
-    ($t:ident) => {
-        pub fn $t<V : Into<CowStr>>(self, val: V) -> Tag {
-            self.attr(stringify!($t), val)
-        }
-    }
-}
+        $(
+            #[allow(non_upper_case_globals)]
+            pub const $t: BareTag<el::$e> = BareTag::new();
 
-macro_rules! impl_attr1 {
-    ($t:ident) => {
-        pub fn $t(self) -> Tag {
-            self.attr1(stringify!($t))
-        }
-    }
-}
-
-macro_rules! impl_attr2 {
-    ($t1:ident, $t2:expr) => {
-        pub fn $t1<V : Into<CowStr>>(self, val: V) -> Tag {
-            self.attr($t2, val)
-        }
-    }
-}
-macro_rules! impl_attr_all {
-    () => {
-        impl_attr!(class);
-        impl_attr!(id);
-        impl_attr!(charset);
-        impl_attr!(content);
-        impl_attr!(name);
-        impl_attr!(href);
-        impl_attr!(rel);
-        impl_attr!(src);
-        impl_attr!(integrity);
-        impl_attr!(crossorigin);
-        impl_attr!(role);
-        impl_attr!(method);
-        impl_attr!(action);
-        impl_attr!(placeholder);
-        impl_attr!(value);
-        impl_attr!(rows);
-        impl_attr!(alt);
-        impl_attr!(style);
-        impl_attr!(onclick);
-        impl_attr!(placement);
-        impl_attr!(toggle);
-        impl_attr!(scope);
-        impl_attr!(title);
-        impl_attr1!(checked);
-        impl_attr1!(enabled);
-        impl_attr1!(disabled);
-        impl_attr2!(type_, "type");
-        impl_attr2!(data_toggle, "data-toggle");
-        impl_attr2!(data_target, "data-target");
-        impl_attr2!(data_placement, "data-placement");
-        impl_attr2!(aria_controls, "aria-controls");
-        impl_attr2!(aria_expanded, "aria-expanded");
-        impl_attr2!(aria_label, "aria-label");
-        impl_attr2!(aria_haspopup, "aria-haspopup");
-        impl_attr2!(aria_labelledby, "aria-labelledby");
-        impl_attr2!(aria_current, "aria-current");
-        impl_attr2!(for_, "for");
+            impl_tags!(@non_void $e, $kind);
+        )*
     };
-}
-
-impl Tag {
-    pub fn attr<K: Into<CowStr>, V: Into<CowStr>>(self, key: K, val: V) -> Tag {
-        let Tag { tag, mut attrs } = self;
-        attrs.push((key.into(), Some(val.into())));
-        Tag {
-            tag: tag,
-            attrs: attrs,
-        }
-    }
-    pub fn attr1<K: Into<CowStr>>(self, key: K) -> Tag {
-        let Tag { tag, mut attrs } = self;
-        attrs.push((key.into(), None));
-        Tag {
-            tag: tag,
-            attrs: attrs,
-        }
-    }
-    impl_attr_all!();
-}
-
-impl BareTag {
-    pub fn attr<K: Into<CowStr>, V: Into<CowStr>>(self, key: K, val: V) -> Tag {
-        Tag {
-            tag: self.tag.into(),
-            attrs: vec![(key.into(), Some(val.into()))],
-        }
-    }
-    pub fn attr1<K: Into<CowStr>>(self, key: K) -> Tag {
-        Tag {
-            tag: self.tag.into(),
-            attrs: vec![(key.into(), None)],
-        }
-    }
-    impl_attr_all!();
-}
-
-impl<A: Render + 'static> FnOnce<(A,)> for Tag {
-    type Output = FinalTag<A>;
-    extern "rust-call" fn call_once(self, args: (A,)) -> Self::Output {
-        FinalTag {
-            tag: self.tag,
-            attrs: self.attrs,
-            inn: args.0,
-        }
-    }
-}
-
-impl<A: Render + 'static> FnOnce<(A,)> for BareTag {
-    type Output = FinalTag<A>;
-    extern "rust-call" fn call_once(self, args: (A,)) -> Self::Output {
-        FinalTag {
-            tag: self.tag.into(),
-            attrs: vec![],
-            inn: args.0,
-        }
-    }
-}
-
-/// Implement a Render function wrapping in a simple tag
-macro_rules! impl_tag {
-    ($t:ident) => {
-        #[allow(non_upper_case_globals)]
-        pub const $t: BareTag = BareTag {
-            tag: stringify!($t),
-        };
+    (@non_void $e:ident, Void) => {};
+    (@non_void $e:ident, $kind:ident) => {
+        impl NonVoid for el::$e {}
     };
 }
 
 pub fn doctype(t: &'static str) -> impl Render {
-    Fn(move |r: &mut super::Renderer| {
+    Fn(move |r: &mut dyn super::Renderer| {
         r.write_raw(b"<!DOCTYPE ")?;
         r.write_raw_str(t)?;
         r.write_raw(b">")
@@ -269,7 +111,7 @@ macro_rules! impl_esc {
         pub const $i: $t = $t;
 
         impl Render for $t {
-            fn render(&self, r: &mut super::Renderer) -> io::Result<()> {
+            fn render(&self, r: &mut dyn super::Renderer) -> io::Result<()> {
                 r.write_raw_str($s)
             }
         }
@@ -280,52 +122,138 @@ impl_esc!(nbsp, Nbsp, "&nbsp;");
 impl_esc!(lt, Lt, "&lt;");
 impl_esc!(gt, Gt, "&gt;");
 
+/// Comment with `x` escaped so it can't break out of it
+pub fn comment<T: Render>(x: T) -> impl Render {
+    Fn(move |r: &mut dyn super::Renderer| {
+        r.write_raw(b"<!--")?;
+        x.render(&mut ContextRenderer::new(r, Context::Comment))?;
+        r.write_raw(b"-->")
+    })
+}
+
+/// Render `x` without any escaping
+///
+/// See `PreEscaped`.
 pub fn raw<T: Render>(x: T) -> impl Render {
-    Fn(move |r: &mut super::Renderer| x.render(&mut super::RawRenderer(r)))
+    Fn(move |r: &mut dyn super::Renderer| x.render(&mut super::RawRenderer(r)))
 }
 
-impl_tag!(html);
-impl_tag!(head);
-impl_tag!(meta);
-impl_tag!(title);
-impl_tag!(body);
-impl_tag!(div);
-impl_tag!(section);
-impl_tag!(h1);
-impl_tag!(h2);
-impl_tag!(h3);
-impl_tag!(h4);
-impl_tag!(h5);
-impl_tag!(li);
-impl_tag!(ul);
-impl_tag!(ol);
-impl_tag!(p);
-impl_tag!(span);
-impl_tag!(b);
-impl_tag!(i);
-impl_tag!(u);
-impl_tag!(tt);
-impl_tag!(string);
-impl_tag!(pre);
-impl_tag!(link);
-impl_tag!(script);
-impl_tag!(main);
-impl_tag!(nav);
-impl_tag!(a);
-impl_tag!(form);
-impl_tag!(button);
-impl_tag!(input);
-impl_tag!(img);
-impl_tag!(blockquote);
-impl_tag!(footer);
-impl_tag!(wrapper);
-impl_tag!(label);
-impl_tag!(table);
-impl_tag!(thead);
-impl_tag!(th);
-impl_tag!(tr);
-impl_tag!(td);
-impl_tag!(tbody);
-impl_tag!(textarea);
+// Elements named like common bindings get a trailing `_`, so that
+// glob-importing the elements doesn't break templates taking eg.
+// `data: &Data` (see `impl_tags!`)
+impl_tags! {
+    a => A: Normal,
+    abbr => Abbr: Normal,
+    address => Address: Normal,
+    area => Area: Void,
+    article => Article: Normal,
+    aside => Aside: Normal,
+    audio => Audio: Normal,
+    b => B: Normal,
+    base => Base: Void,
+    bdi => Bdi: Normal,
+    bdo => Bdo: Normal,
+    blockquote => Blockquote: Normal,
+    body => Body: Normal,
+    br => Br: Void,
+    button => Button: Normal,
+    canvas => Canvas: Normal,
+    caption => Caption: Normal,
+    cite => Cite: Normal,
+    code_ = "code" => Code: Normal,
+    col => Col: Void,
+    colgroup => Colgroup: Normal,
+    data_ = "data" => Data: Normal,
+    datalist => Datalist: Normal,
+    dd => Dd: Normal,
+    del => Del: Normal,
+    details_ = "details" => Details: Normal,
+    dfn => Dfn: Normal,
+    dialog => Dialog: Normal,
+    div => Div: Normal,
+    dl => Dl: Normal,
+    dt => Dt: Normal,
+    em => Em: Normal,
+    embed => Embed: Void,
+    fieldset => Fieldset: Normal,
+    figcaption => Figcaption: Normal,
+    figure => Figure: Normal,
+    footer => Footer: Normal,
+    form => Form: Normal,
+    h1 => H1: Normal,
+    h2 => H2: Normal,
+    h3 => H3: Normal,
+    h4 => H4: Normal,
+    h5 => H5: Normal,
+    h6 => H6: Normal,
+    head => Head: Normal,
+    header_ = "header" => Header: Normal,
+    hgroup => Hgroup: Normal,
+    hr => Hr: Void,
+    html => Html: Normal,
+    i => I: Normal,
+    iframe => Iframe: Normal,
+    img => Img: Void,
+    input => Input: Void,
+    ins => Ins: Normal,
+    kbd => Kbd: Normal,
+    label => Label: Normal,
+    legend => Legend: Normal,
+    li => Li: Normal,
+    link => Link: Void,
+    main => Main: Normal,
+    map_ = "map" => Map: Normal,
+    mark => Mark: Normal,
+    menu => Menu: Normal,
+    meta => Meta: Void,
+    meter => Meter: Normal,
+    nav => Nav: Normal,
+    noscript => Noscript: Normal,
+    object_ = "object" => Object: Normal,
+    ol => Ol: Normal,
+    optgroup => Optgroup: Normal,
+    option_ = "option" => Option: Normal,
+    output_ = "output" => Output: Normal,
+    p => P: Normal,
+    picture => Picture: Normal,
+    pre => Pre: Normal,
+    progress_ = "progress" => Progress: Normal,
+    q_ = "q" => Q: Normal,
+    rp => Rp: Normal,
+    rt => Rt: Normal,
+    ruby => Ruby: Normal,
+    s_ = "s" => S: Normal,
+    samp => Samp: Normal,
+    script => Script: RawText,
+    search_ = "search" => Search: Normal,
+    section => Section: Normal,
+    select => Select: Normal,
+    slot_ = "slot" => Slot: Normal,
+    small => Small: Normal,
+    source_ = "source" => Source: Void,
+    span => Span: Normal,
+    strong => Strong: Normal,
+    style_ = "style" => Style: RawText,
+    sub => Sub: Normal,
+    summary_ = "summary" => Summary: Normal,
+    sup => Sup: Normal,
+    table => Table: Normal,
+    tbody => Tbody: Normal,
+    td => Td: Normal,
+    template_ = "template" => Template: Normal,
+    textarea => Textarea: EscapableRawText,
+    tfoot => Tfoot: Normal,
+    th => Th: Normal,
+    thead => Thead: Normal,
+    time_ = "time" => Time: Normal,
+    title => Title: EscapableRawText,
+    tr => Tr: Normal,
+    track => Track: Void,
+    u => U: Normal,
+    ul => Ul: Normal,
+    var => Var: Normal,
+    video => Video: Normal,
+    wbr => Wbr: Void,
+}
 
 // vim: foldmethod=marker foldmarker={{{,}}}
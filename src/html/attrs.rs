@@ -0,0 +1,488 @@
+use super::el;
+use super::values::*;
+use super::{BareTag, Element, IntoAttrValue, Tag};
+
+macro_rules! impl_attr {
+    ($e:ty, $t:ident) => {
+        pub fn $t<V: IntoAttrValue>(self, val: V) -> Tag<$e> {
+            self.attr(stringify!($t), val)
+        }
+    };
+}
+
+macro_rules! impl_attr1 {
+    ($e:ty, $t1:ident, $t2:expr) => {
+        pub fn $t1(self, on: bool) -> Tag<$e> {
+            let tag = Tag::from(self);
+            if on {
+                tag.attr1($t2)
+            } else {
+                tag.remove_attr($t2)
+            }
+        }
+    };
+}
+
+macro_rules! impl_attr2 {
+    ($e:ty, $t1:ident, $t2:expr) => {
+        pub fn $t1<V: IntoAttrValue>(self, val: V) -> Tag<$e> {
+            self.attr($t2, val)
+        }
+    };
+}
+
+macro_rules! impl_attr_typed {
+    ($e:ty, $t1:ident, $t2:expr, $v:ty) => {
+        pub fn $t1<V: Into<Option<$v>>>(self, val: V) -> Tag<$e> {
+            self.attr($t2, val.into())
+        }
+    };
+}
+
+/// Implement attribute methods from a list
+///
+/// Each attribute is one of:
+///
+/// * `name` - attribute with a value (or `Option` of it, `None` leaves it out),
+/// * `name = "attr-name"` - as above, with a different name in the markup,
+/// * `name: Type` - attribute with a typed value,
+/// * `name = "attr-name": Type` - as above, with a different name in the markup,
+/// * `name?` - boolean attribute, set if `true`,
+/// * `name = "attr-name"?` - as above, with a different name in the markup.
+macro_rules! impl_attrs {
+    ($e:ty;) => {};
+    ($e:ty; $t:ident = $n:literal: $v:ty, $($rest:tt)*) => {
+        impl_attr_typed!($e, $t, $n, $v);
+        impl_attrs!($e; $($rest)*);
+    };
+    ($e:ty; $t:ident = $n:literal?, $($rest:tt)*) => {
+        impl_attr1!($e, $t, $n);
+        impl_attrs!($e; $($rest)*);
+    };
+    ($e:ty; $t:ident = $n:literal, $($rest:tt)*) => {
+        impl_attr2!($e, $t, $n);
+        impl_attrs!($e; $($rest)*);
+    };
+    ($e:ty; $t:ident: $v:ty, $($rest:tt)*) => {
+        impl_attr_typed!($e, $t, stringify!($t), $v);
+        impl_attrs!($e; $($rest)*);
+    };
+    ($e:ty; $t:ident?, $($rest:tt)*) => {
+        impl_attr1!($e, $t, stringify!($t));
+        impl_attrs!($e; $($rest)*);
+    };
+    ($e:ty; $t:ident, $($rest:tt)*) => {
+        impl_attr!($e, $t);
+        impl_attrs!($e; $($rest)*);
+    };
+}
+
+/// Attributes that every element accepts
+macro_rules! impl_attrs_global {
+    ($e:ty) => {
+        impl_attrs! { $e;
+            accesskey,
+            autocapitalize,
+            autofocus?,
+            contenteditable,
+            dir: Dir,
+            draggable,
+            enterkeyhint,
+            hidden?,
+            id,
+            inert?,
+            inputmode,
+            is,
+            itemid,
+            itemprop,
+            itemref,
+            itemscope?,
+            itemtype,
+            lang,
+            nonce,
+            popover,
+            role,
+            slot,
+            spellcheck,
+            style,
+            tabindex,
+            title,
+            translate,
+            onblur,
+            onchange,
+            onclick,
+            ondblclick,
+            onfocus,
+            oninput,
+            onkeydown,
+            onkeyup,
+            onload,
+            onmousedown,
+            onmouseout,
+            onmouseover,
+            onmouseup,
+            onsubmit,
+            data_toggle = "data-toggle",
+            data_target = "data-target",
+            data_placement = "data-placement",
+            aria_controls = "aria-controls",
+            aria_expanded = "aria-expanded",
+            aria_label = "aria-label",
+            aria_haspopup = "aria-haspopup",
+            aria_labelledby = "aria-labelledby",
+            aria_current = "aria-current",
+        }
+    };
+}
+
+impl<E: Element> Tag<E> {
+    impl_attrs_global!(E);
+}
+
+impl<E: Element> BareTag<E> {
+    impl_attrs_global!(E);
+}
+
+/// Implement element-specific attributes, for both `BareTag` and `Tag`
+macro_rules! impl_element_attrs {
+    ($($e:ident { $($attrs:tt)* })*) => {
+        $(
+            impl Tag<el::$e> {
+                impl_attrs! { el::$e; $($attrs)* }
+            }
+
+            impl BareTag<el::$e> {
+                impl_attrs! { el::$e; $($attrs)* }
+            }
+        )*
+    };
+}
+
+impl_element_attrs! {
+    A {
+        download,
+        href,
+        hreflang,
+        ping,
+        referrerpolicy: ReferrerPolicy,
+        rel,
+        target,
+        type_ = "type",
+    }
+    Area {
+        alt,
+        coords,
+        download,
+        href,
+        ping,
+        referrerpolicy: ReferrerPolicy,
+        rel,
+        shape,
+        target,
+    }
+    Audio {
+        autoplay?,
+        controls?,
+        crossorigin: CrossOrigin,
+        loop_ = "loop"?,
+        muted?,
+        preload: Preload,
+        src,
+    }
+    Base {
+        href,
+        target,
+    }
+    Blockquote {
+        cite,
+    }
+    Button {
+        disabled?,
+        form,
+        formaction,
+        formenctype: Enctype,
+        formmethod: Method,
+        formnovalidate?,
+        formtarget,
+        name,
+        popovertarget,
+        popovertargetaction,
+        type_ = "type": ButtonType,
+        value,
+    }
+    Canvas {
+        height,
+        width,
+    }
+    Col {
+        span,
+    }
+    Colgroup {
+        span,
+    }
+    Data {
+        value,
+    }
+    Del {
+        cite,
+        datetime,
+    }
+    Details {
+        name,
+        open?,
+    }
+    Dialog {
+        open?,
+    }
+    Embed {
+        height,
+        src,
+        type_ = "type",
+        width,
+    }
+    Fieldset {
+        disabled?,
+        form,
+        name,
+    }
+    Form {
+        accept_charset = "accept-charset",
+        action,
+        autocomplete,
+        enctype: Enctype,
+        method: Method,
+        name,
+        novalidate?,
+        rel,
+        target,
+    }
+    Iframe {
+        allow,
+        allowfullscreen?,
+        height,
+        loading: Loading,
+        name,
+        referrerpolicy: ReferrerPolicy,
+        sandbox,
+        src,
+        srcdoc,
+        width,
+    }
+    Img {
+        alt,
+        crossorigin: CrossOrigin,
+        decoding: Decoding,
+        fetchpriority: FetchPriority,
+        height,
+        ismap?,
+        loading: Loading,
+        referrerpolicy: ReferrerPolicy,
+        sizes,
+        src,
+        srcset,
+        usemap,
+        width,
+    }
+    Input {
+        accept,
+        alt,
+        autocomplete,
+        checked?,
+        dirname,
+        disabled?,
+        form,
+        formaction,
+        formenctype: Enctype,
+        formmethod: Method,
+        formnovalidate?,
+        formtarget,
+        height,
+        list,
+        max,
+        maxlength,
+        min,
+        minlength,
+        multiple?,
+        name,
+        pattern,
+        placeholder,
+        popovertarget,
+        popovertargetaction,
+        readonly?,
+        required?,
+        size,
+        src,
+        step,
+        type_ = "type": InputType,
+        value,
+        width,
+    }
+    Ins {
+        cite,
+        datetime,
+    }
+    Label {
+        for_ = "for",
+    }
+    Li {
+        value,
+    }
+    Link {
+        as_ = "as",
+        blocking,
+        color,
+        crossorigin: CrossOrigin,
+        disabled?,
+        fetchpriority: FetchPriority,
+        href,
+        hreflang,
+        imagesizes,
+        imagesrcset,
+        integrity,
+        media,
+        referrerpolicy: ReferrerPolicy,
+        rel,
+        sizes,
+        type_ = "type",
+    }
+    Map {
+        name,
+    }
+    Meta {
+        charset,
+        content,
+        http_equiv = "http-equiv",
+        media,
+        name,
+    }
+    Meter {
+        high,
+        low,
+        max,
+        min,
+        optimum,
+        value,
+    }
+    Object {
+        data_ = "data",
+        form,
+        height,
+        name,
+        type_ = "type",
+        width,
+    }
+    Ol {
+        reversed?,
+        start,
+        type_ = "type",
+    }
+    Optgroup {
+        disabled?,
+        label,
+    }
+    Option {
+        disabled?,
+        label,
+        selected?,
+        value,
+    }
+    Output {
+        for_ = "for",
+        form,
+        name,
+    }
+    Progress {
+        max,
+        value,
+    }
+    Q {
+        cite,
+    }
+    Script {
+        async_ = "async"?,
+        blocking,
+        crossorigin: CrossOrigin,
+        defer?,
+        fetchpriority: FetchPriority,
+        integrity,
+        nomodule?,
+        referrerpolicy: ReferrerPolicy,
+        src,
+        type_ = "type",
+    }
+    Select {
+        autocomplete,
+        disabled?,
+        form,
+        multiple?,
+        name,
+        required?,
+        size,
+    }
+    Slot {
+        name,
+    }
+    Source {
+        height,
+        media,
+        sizes,
+        src,
+        srcset,
+        type_ = "type",
+        width,
+    }
+    Style {
+        blocking,
+        media,
+    }
+    Td {
+        colspan,
+        headers,
+        rowspan,
+    }
+    Textarea {
+        autocomplete,
+        cols,
+        dirname,
+        disabled?,
+        form,
+        maxlength,
+        minlength,
+        name,
+        placeholder,
+        readonly?,
+        required?,
+        rows,
+        wrap: Wrap,
+    }
+    Th {
+        abbr,
+        colspan,
+        headers,
+        rowspan,
+        scope: Scope,
+    }
+    Time {
+        datetime,
+    }
+    Track {
+        default?,
+        kind: TrackKind,
+        label,
+        src,
+        srclang,
+    }
+    Video {
+        autoplay?,
+        controls?,
+        crossorigin: CrossOrigin,
+        height,
+        loop_ = "loop"?,
+        muted?,
+        playsinline?,
+        poster,
+        preload: Preload,
+        src,
+        width,
+    }
+}
@@ -0,0 +1,294 @@
+use std::{fmt, io};
+
+use {Context, Renderer};
+
+impl<T: io::Write> ::Renderer for T {
+    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.write_all(data)
+    }
+
+    fn write_raw_fmt(&mut self, fmt: &fmt::Arguments) -> io::Result<()> {
+        self.write_fmt(*fmt)
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        escape_text(self, data)
+    }
+
+    fn write_ctx(&mut self, ctx: Context, data: &[u8]) -> io::Result<()> {
+        match ctx {
+            Context::Text => escape_text(self, data),
+            Context::Attr => escape_attr(self, data),
+            Context::UrlAttr => escape_url_attr(self, data),
+            Context::Script => escape_raw_text(self, data, b"script"),
+            Context::Style => escape_raw_text(self, data, b"style"),
+            Context::Comment => escape_comment(self, data),
+        }
+    }
+}
+
+// {{{ escaping
+fn escape_text<W: io::Write + ?Sized>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    for c in data {
+        match *c as char {
+            '&' => w.write_all(b"&amp;")?,
+            '<' => w.write_all(b"&lt;")?,
+            '>' => w.write_all(b"&gt;")?,
+            '"' => w.write_all(b"&quot;")?,
+            '\'' => w.write_all(b"&#x27;")?,
+            '/' => w.write_all(b"&#x2F;")?,
+            // Additional one for old IE (unpatched IE8 and below)
+            // See https://github.com/OWASP/owasp-java-encoder/wiki/Grave-Accent-Issue
+            '`' => w.write_all(b"&#96;")?,
+            _ => w.write_all(&[*c])?,
+        }
+    }
+
+    Ok(())
+}
+
+fn escape_attr<W: io::Write + ?Sized>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    for c in data {
+        match *c as char {
+            '&' => w.write_all(b"&amp;")?,
+            '<' => w.write_all(b"&lt;")?,
+            '>' => w.write_all(b"&gt;")?,
+            '"' => w.write_all(b"&quot;")?,
+            '\'' => w.write_all(b"&#x27;")?,
+            '`' => w.write_all(b"&#96;")?,
+            _ => w.write_all(&[*c])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// URL schemes that are safe to follow
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "tel", "ftp"];
+
+/// `data:` URL media types that are safe to follow; SVG is not, since
+/// it can contain scripts
+const SAFE_DATA_URL_TYPES: &[&str] = &[
+    "image/png",
+    "image/gif",
+    "image/jpeg",
+    "image/webp",
+    "image/avif",
+    "image/bmp",
+];
+
+/// Replacement for URLs with a scheme that is not known to be safe
+const UNSAFE_URL: &[u8] = b"about:invalid#stpl-unsafe-url";
+
+fn is_safe_url(data: &[u8]) -> bool {
+    // Browsers ignore leading whitespace and control characters
+    let data: Vec<u8> = data
+        .iter()
+        .skip_while(|c| **c <= b' ')
+        .filter(|c| !(**c == b'\t' || **c == b'\n' || **c == b'\r'))
+        .cloned()
+        .collect();
+    match data.iter().position(|c| b":/?#".contains(c)) {
+        Some(pos) if data[pos] == b':' => {
+            let scheme = String::from_utf8_lossy(&data[..pos]).to_lowercase();
+            if scheme == "data" {
+                let media_type = String::from_utf8_lossy(&data[pos + 1..]).to_lowercase();
+                let media_type = media_type.split([';', ',']).next().unwrap_or("");
+                return SAFE_DATA_URL_TYPES.contains(&media_type);
+            }
+            SAFE_URL_SCHEMES.contains(&&*scheme)
+        }
+        _ => true,
+    }
+}
+
+/// Escape an URL for a quoted attribute
+///
+/// URLs with schemes that are not known to be safe (eg. `javascript:`)
+/// are replaced altogether, as are `data:` URLs other than raster
+/// images. The data is expected to be a whole URL.
+fn escape_url_attr<W: io::Write + ?Sized>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    if !is_safe_url(data) {
+        return w.write_all(UNSAFE_URL);
+    }
+    for c in data {
+        match *c {
+            b'&' => w.write_all(b"&amp;")?,
+            c if c <= b' ' || c >= 0x7f => write!(w, "%{:02X}", c)?,
+            b'"' | b'\'' | b'<' | b'>' | b'`' | b'\\' => write!(w, "%{:02X}", c)?,
+            c => w.write_all(&[c])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Escape for the inside of a JavaScript string literal
+fn escape_js_string<W: io::Write + ?Sized>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    let mut pos = 0;
+    while pos < data.len() {
+        match data[pos] {
+            b'\\' => w.write_all(b"\\\\")?,
+            b'\'' => w.write_all(b"\\'")?,
+            b'"' => w.write_all(b"\\\"")?,
+            b'`' => w.write_all(b"\\`")?,
+            b'\n' => w.write_all(b"\\n")?,
+            b'\r' => w.write_all(b"\\r")?,
+            b'\t' => w.write_all(b"\\t")?,
+            // Prevent `</script>`, `<!--` and entities from being recognized
+            c @ b'<' | c @ b'>' | c @ b'&' => write!(w, "\\u{:04X}", c)?,
+            c if c < b' ' || c == 0x7f => write!(w, "\\u{:04X}", c)?,
+            // U+2028 and U+2029 are line terminators in older JavaScript
+            0xe2 if data.get(pos + 1) == Some(&0x80)
+                && (data.get(pos + 2) == Some(&0xa8) || data.get(pos + 2) == Some(&0xa9)) =>
+            {
+                write!(w, "\\u{:04X}", 0x2028 + u32::from(data[pos + 2] - 0xa8))?;
+                pos += 2;
+            }
+            c => w.write_all(&[c])?,
+        }
+        pos += 1;
+    }
+
+    Ok(())
+}
+
+/// Quote `s` as a JavaScript string, escaping it as necessary
+///
+/// Use for values placed inside `<script>`, whose content is otherwise
+/// only kept from closing the element early.
+pub fn js_string(s: &str) -> String {
+    let mut out = Vec::with_capacity(s.len() + 2);
+    out.push(b'"');
+    escape_js_string(&mut out, s.as_bytes()).expect("writing to `Vec` can't fail");
+    out.push(b'"');
+    String::from_utf8(out).expect("escaping keeps UTF-8 valid")
+}
+
+/// Escape the content of raw text element `tag`, so it can't be
+/// closed early
+///
+/// Only `</tag` and `<!--` are neutralised, by escaping the `/` and `!`
+/// with a backslash, which keeps the meaning of JavaScript and CSS
+/// strings. A `/tag` or `!--` at the beginning of `data` is
+/// escaped too, since the `<` might have been written just before.
+fn escape_raw_text<W: io::Write + ?Sized>(w: &mut W, data: &[u8], tag: &[u8]) -> io::Result<()> {
+    let starts_with = |pos: usize, s: &[u8]| {
+        data.len() >= pos + s.len() && data[pos..pos + s.len()].eq_ignore_ascii_case(s)
+    };
+    for (pos, &c) in data.iter().enumerate() {
+        let after_lt = pos == 0 || data[pos - 1] == b'<';
+        match c {
+            b'/' if after_lt && starts_with(pos + 1, tag) => w.write_all(b"\\/")?,
+            b'!' if after_lt && starts_with(pos + 1, b"--") => w.write_all(b"\\!")?,
+            c => w.write_all(&[c])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// `Renderer` for the content of raw text elements (`<script>`,
+/// `<style>`)
+///
+/// Normal writes are buffered and escaped together, so an end tag
+/// split across several writes (eg. `("</scr", "ipt>")`) is still
+/// caught. Raw and other context writes flush the buffer first.
+pub(crate) struct RawTextRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    ctx: Context,
+    buf: Vec<u8>,
+}
+
+impl<'a> RawTextRenderer<'a> {
+    pub(crate) fn new(inner: &'a mut dyn Renderer, ctx: Context) -> Self {
+        RawTextRenderer {
+            inner,
+            ctx,
+            buf: vec![],
+        }
+    }
+
+    /// Write out the buffered content, escaped
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_ctx(self.ctx, &self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<'a> Renderer for RawTextRenderer<'a> {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.buf.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn write_ctx(&mut self, ctx: Context, data: &[u8]) -> io::Result<()> {
+        if ctx == self.ctx {
+            return self.write(data);
+        }
+        self.flush()?;
+        self.inner.write_ctx(ctx, data)
+    }
+
+    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.flush()?;
+        self.inner.write_raw(data)
+    }
+}
+
+/// Escape for the inside of a comment, so it can't be terminated early
+fn escape_comment<W: io::Write + ?Sized>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    for c in data {
+        match *c {
+            b'<' => w.write_all(b"&lt;")?,
+            b'>' => w.write_all(b"&gt;")?,
+            b'-' => w.write_all(b"&#45;")?,
+            b'!' => w.write_all(b"&#33;")?,
+            c => w.write_all(&[c])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Attributes holding URLs
+const URL_ATTRS: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "codebase",
+    "data",
+    "formaction",
+    "href",
+    "icon",
+    "longdesc",
+    "manifest",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Context that the value of attribute `name` should be escaped for
+pub(crate) fn attr_context(name: &str) -> Context {
+    if URL_ATTRS.iter().any(|attr| attr.eq_ignore_ascii_case(name)) {
+        Context::UrlAttr
+    } else {
+        Context::Attr
+    }
+}
+
+/// Context that the content of raw text element `tag` should be escaped for
+pub(crate) fn content_context(tag: &str) -> Context {
+    if tag.eq_ignore_ascii_case("script") {
+        Context::Script
+    } else if tag.eq_ignore_ascii_case("style") {
+        Context::Style
+    } else {
+        Context::Text
+    }
+}
+// }}}
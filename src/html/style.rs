@@ -0,0 +1,292 @@
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+
+use super::AttrValue;
+
+type CowStr = Cow<'static, str>;
+
+/// Style declaration that was rejected by `Style`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidStyle {
+    pub name: String,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid style declaration: {:?}: {:?}",
+            self.name, self.value
+        )
+    }
+}
+
+impl error::Error for InvalidStyle {}
+
+/// Is `name` a plain CSS property name (including custom `--*` ones)
+fn is_valid_property(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `value` with CSS escapes decoded, eg. `\6a ` as `j`
+fn decode_escapes(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let mut code = 0;
+        let mut len = 0;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+            code = code * 16 + digit;
+            len += 1;
+            chars.next();
+            if len == 6 {
+                break;
+            }
+        }
+        if len == 0 {
+            // Any other character stands for itself
+            out.extend(chars.next());
+            continue;
+        }
+        // A whitespace after a hex escape ends it
+        if chars.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            chars.next();
+        }
+        out.push(
+            char::from_u32(code)
+                .filter(|&c| c != '\0')
+                .unwrap_or('\u{fffd}'),
+        );
+    }
+    out
+}
+
+/// Is `value` safe to put in a declaration
+///
+/// Rejects anything that could end the declaration or the rule, start
+/// a comment, or end the attribute the style is placed in. Backslashes
+/// are only allowed as hex escapes (see `css_string`), which are
+/// decoded before looking for `javascript:` and `expression(`.
+fn is_valid_value(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    let decoded = decode_escapes(&lower).to_ascii_lowercase();
+    !value.trim().is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_control() || [';', '{', '}', '<', '>', '"'].contains(&c))
+        && value
+            .split('\\')
+            .skip(1)
+            .all(|s| s.starts_with(|c: char| c.is_ascii_hexdigit()))
+        && value.matches('\'').count().is_multiple_of(2)
+        && !lower.contains("/*")
+        && !lower.contains("*/")
+        && !decoded.contains("expression(")
+        && !decoded.contains("javascript:")
+}
+
+/// Quote `s` as a CSS string, escaping it as necessary
+///
+/// Use for values like `font-family` or `content` that come from users.
+pub fn css_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('\'');
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_' || !c.is_ascii() {
+            out.push(c);
+        } else {
+            // Trailing space terminates the escape sequence
+            out.push_str(&format!("\\{:X} ", c as u32));
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// Inline style builder for the `style` attribute
+///
+/// ```ignore
+/// div.style(Style::new().display("flex").margin_px(4))
+/// ```
+///
+/// Setting a property twice overrides the previous value. Declarations
+/// with values that could break out of the declaration (or the attribute)
+/// are rejected: `try_prop` returns an error, other methods skip them
+/// (and panic in debug builds).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    decls: Vec<(CowStr, String)>,
+}
+
+macro_rules! impl_props {
+    ($($t:ident = $n:expr,)*) => {
+        $(
+            pub fn $t<V: AsRef<str>>(self, val: V) -> Style {
+                self.prop($n, val)
+            }
+        )*
+    };
+}
+
+macro_rules! impl_length_props {
+    ($($t:ident, $px:ident, $pct:ident = $n:expr,)*) => {
+        $(
+            impl_props!($t = $n,);
+
+            pub fn $px<N: fmt::Display>(self, val: N) -> Style {
+                self.prop($n, format!("{}px", val))
+            }
+
+            pub fn $pct<N: fmt::Display>(self, val: N) -> Style {
+                self.prop($n, format!("{}%", val))
+            }
+        )*
+    };
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Style::default()
+    }
+
+    /// Set property, checking the declaration
+    pub fn try_prop<K: Into<CowStr>, V: AsRef<str>>(
+        mut self,
+        name: K,
+        val: V,
+    ) -> Result<Style, InvalidStyle> {
+        self.set(name.into(), val.as_ref())?;
+        Ok(self)
+    }
+
+    /// Set property
+    ///
+    /// Invalid declarations are skipped; in debug builds they panic.
+    pub fn prop<K: Into<CowStr>, V: AsRef<str>>(mut self, name: K, val: V) -> Style {
+        if let Err(e) = self.set(name.into(), val.as_ref()) {
+            debug_assert!(false, "{}", e);
+        }
+        self
+    }
+
+    fn set(&mut self, name: CowStr, val: &str) -> Result<(), InvalidStyle> {
+        let val = val.trim();
+        if !is_valid_property(&name) || !is_valid_value(val) {
+            return Err(InvalidStyle {
+                name: name.into_owned(),
+                value: val.to_owned(),
+            });
+        }
+        match self
+            .decls
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case(&name))
+        {
+            Some(pos) => self.decls[pos].1 = val.to_owned(),
+            None => self.decls.push((name, val.to_owned())),
+        }
+        Ok(())
+    }
+
+    /// Set property if `cond` is true
+    pub fn prop_if<K: Into<CowStr>, V: AsRef<str>>(self, cond: bool, name: K, val: V) -> Style {
+        if cond {
+            self.prop(name, val)
+        } else {
+            self
+        }
+    }
+
+    /// Set all the properties of `other`, overriding the ones already set
+    pub fn merge(self, other: Style) -> Style {
+        other
+            .decls
+            .into_iter()
+            .fold(self, |style, (k, v)| style.prop(k, v))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decls.is_empty()
+    }
+
+    impl_props! {
+        align_items = "align-items",
+        background = "background",
+        background_color = "background-color",
+        border = "border",
+        color = "color",
+        cursor = "cursor",
+        display = "display",
+        flex = "flex",
+        flex_direction = "flex-direction",
+        flex_wrap = "flex-wrap",
+        float = "float",
+        font_family = "font-family",
+        font_style = "font-style",
+        font_weight = "font-weight",
+        justify_content = "justify-content",
+        line_height = "line-height",
+        opacity = "opacity",
+        overflow = "overflow",
+        position = "position",
+        text_align = "text-align",
+        text_decoration = "text-decoration",
+        transform = "transform",
+        visibility = "visibility",
+        white_space = "white-space",
+        z_index = "z-index",
+    }
+
+    impl_length_props! {
+        border_radius, border_radius_px, border_radius_pct = "border-radius",
+        bottom, bottom_px, bottom_pct = "bottom",
+        font_size, font_size_px, font_size_pct = "font-size",
+        gap, gap_px, gap_pct = "gap",
+        height, height_px, height_pct = "height",
+        left, left_px, left_pct = "left",
+        margin, margin_px, margin_pct = "margin",
+        margin_bottom, margin_bottom_px, margin_bottom_pct = "margin-bottom",
+        margin_left, margin_left_px, margin_left_pct = "margin-left",
+        margin_right, margin_right_px, margin_right_pct = "margin-right",
+        margin_top, margin_top_px, margin_top_pct = "margin-top",
+        max_height, max_height_px, max_height_pct = "max-height",
+        max_width, max_width_px, max_width_pct = "max-width",
+        min_height, min_height_px, min_height_pct = "min-height",
+        min_width, min_width_px, min_width_pct = "min-width",
+        padding, padding_px, padding_pct = "padding",
+        padding_bottom, padding_bottom_px, padding_bottom_pct = "padding-bottom",
+        padding_left, padding_left_px, padding_left_pct = "padding-left",
+        padding_right, padding_right_px, padding_right_pct = "padding-right",
+        padding_top, padding_top_px, padding_top_pct = "padding-top",
+        right, right_px, right_pct = "right",
+        top, top_px, top_pct = "top",
+        width, width_px, width_pct = "width",
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (k, v)) in self.decls.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Style> for AttrValue {
+    fn from(style: Style) -> Self {
+        AttrValue::Text(style.to_string().into())
+    }
+}
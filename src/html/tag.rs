@@ -0,0 +1,631 @@
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use super::escape::{attr_context, content_context, RawTextRenderer};
+use {Context, Error, RawRenderer, Render, Renderer};
+
+type CowStr = Cow<'static, str>;
+
+/// Trusted value that will be rendered without any escaping
+///
+/// Works both for attribute values and for content (trusted markup).
+/// Only use it for data that is known to be safe in the place
+/// it is going to be rendered in.
+pub struct PreEscaped<T>(pub T);
+
+impl<T: Render> Render for PreEscaped<T> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        self.0.render(&mut RawRenderer(r))
+    }
+}
+
+/// Value of a tag attribute
+///
+/// Unless wrapped in `PreEscaped`, attribute values are escaped
+/// when rendered.
+pub enum AttrValue {
+    /// Value that will be escaped
+    Text(CowStr),
+    /// Trusted value that will be written as-is
+    PreEscaped(CowStr),
+}
+
+impl From<&'static str> for AttrValue {
+    fn from(s: &'static str) -> Self {
+        AttrValue::Text(s.into())
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(s: String) -> Self {
+        AttrValue::Text(s.into())
+    }
+}
+
+impl From<CowStr> for AttrValue {
+    fn from(s: CowStr) -> Self {
+        AttrValue::Text(s)
+    }
+}
+
+impl<T: Into<CowStr>> From<PreEscaped<T>> for AttrValue {
+    fn from(s: PreEscaped<T>) -> Self {
+        AttrValue::PreEscaped(s.0.into())
+    }
+}
+
+impl AttrValue {
+    /// The value, as it was set (before escaping)
+    pub fn as_str(&self) -> &str {
+        match *self {
+            AttrValue::Text(ref s) | AttrValue::PreEscaped(ref s) => s,
+        }
+    }
+
+    fn render(&self, ctx: Context, r: &mut dyn Renderer) -> io::Result<()> {
+        match *self {
+            AttrValue::Text(ref s) => r.write_ctx_str(ctx, s),
+            AttrValue::PreEscaped(ref s) => r.write_raw_str(s),
+        }
+    }
+
+    fn is_pre_escaped(&self) -> bool {
+        match *self {
+            AttrValue::Text(_) => false,
+            AttrValue::PreEscaped(_) => true,
+        }
+    }
+}
+
+/// Value accepted by attribute methods
+///
+/// Anything convertible to `AttrValue`, or an `Option` of it, where
+/// `None` means the attribute is left out.
+pub trait IntoAttrValue {
+    fn into_attr_value(self) -> Option<AttrValue>;
+}
+
+impl<T: Into<AttrValue>> IntoAttrValue for T {
+    fn into_attr_value(self) -> Option<AttrValue> {
+        Some(self.into())
+    }
+}
+
+impl<T: Into<AttrValue>> IntoAttrValue for Option<T> {
+    fn into_attr_value(self) -> Option<AttrValue> {
+        self.map(Into::into)
+    }
+}
+
+type Attrs = Vec<(CowStr, Option<AttrValue>)>;
+
+/// Is `name` a valid attribute name
+///
+/// See https://html.spec.whatwg.org/multipage/syntax.html#attributes-2
+pub fn is_valid_attr_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| {
+            let n = c as u32;
+            !(c.is_control()
+                || [' ', '"', '\'', '>', '/', '='].contains(&c)
+                || (0xfdd0..=0xfdef).contains(&n)
+                || n & 0xfffe == 0xfffe)
+        })
+}
+
+/// Attribute name that is not valid
+///
+/// Returned by the checked `try_attr` API. Rendering a tag with such
+/// attribute fails with `Error::InvalidAttribute`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidAttrName(pub CowStr);
+
+impl fmt::Display for InvalidAttrName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid attribute name: {:?}", self.0)
+    }
+}
+
+impl error::Error for InvalidAttrName {}
+
+fn check_attr_name(name: CowStr) -> Result<CowStr, InvalidAttrName> {
+    if is_valid_attr_name(&name) {
+        Ok(name)
+    } else {
+        Err(InvalidAttrName(name))
+    }
+}
+
+/// `s` escaped for a (non-URL) attribute
+fn escape_attr(s: &str) -> String {
+    let mut out = vec![];
+    out.write_ctx_str(Context::Attr, s)
+        .expect("writing to `Vec` can't fail");
+    String::from_utf8(out).expect("escaping keeps UTF-8 valid")
+}
+
+/// Parts of attribute values to merge, each with whether it's
+/// `PreEscaped`, as strings to join and whether the result is
+/// `PreEscaped`
+///
+/// If only one of them is `PreEscaped`, the other one is escaped, so
+/// the trusted part is not escaped a second time.
+fn merge_parts<'a>(
+    old: (&'a str, bool),
+    new: (&'a str, bool),
+) -> (Cow<'a, str>, Cow<'a, str>, bool) {
+    if !old.1 && !new.1 {
+        return (old.0.into(), new.0.into(), false);
+    }
+    let escaped = |(s, pre_escaped): (&'a str, bool)| {
+        if pre_escaped {
+            Cow::Borrowed(s)
+        } else {
+            Cow::Owned(escape_attr(s))
+        }
+    };
+    (escaped(old), escaped(new), true)
+}
+
+/// Merged value built from `merge_parts`
+fn merged_value(merged: String, pre_escaped: bool) -> AttrValue {
+    if pre_escaped {
+        AttrValue::PreEscaped(merged.into())
+    } else {
+        AttrValue::Text(merged.into())
+    }
+}
+
+/// `style` attribute value without trailing `;`s
+///
+/// In a `PreEscaped` value, a `;` ending a character reference
+/// (eg. `&quot;`) is kept.
+fn trim_style(val: &AttrValue) -> &str {
+    let mut s = val.as_str().trim_end();
+    while let Some(rest) = s.strip_suffix(';') {
+        let is_reference = val.is_pre_escaped()
+            && rest.rfind('&').is_some_and(|pos| {
+                let name = &rest[pos + 1..];
+                !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'#')
+            });
+        if is_reference {
+            break;
+        }
+        s = rest.trim_end();
+    }
+    s
+}
+
+/// Value of `style` attribute set twice
+fn merge_style(old: &AttrValue, new: AttrValue) -> AttrValue {
+    let old_str = trim_style(old);
+    if old_str.is_empty() {
+        return new;
+    }
+    let (old_str, new_str, pre_escaped) = merge_parts(
+        (old_str, old.is_pre_escaped()),
+        (new.as_str(), new.is_pre_escaped()),
+    );
+    merged_value(format!("{}; {}", old_str, new_str), pre_escaped)
+}
+
+/// Value of `class` attribute with classes from `new` added to `old`,
+/// skipping duplicates; `None` if there's nothing to add
+fn merge_class(old: Option<&AttrValue>, new: &AttrValue) -> Option<AttrValue> {
+    let (old_str, new_str, pre_escaped) = merge_parts(
+        old.map_or(("", false), |old| (old.as_str(), old.is_pre_escaped())),
+        (new.as_str(), new.is_pre_escaped()),
+    );
+    let mut classes: Vec<&str> = old_str.split_whitespace().collect();
+    let len = classes.len();
+    for c in new_str.split_whitespace() {
+        if !classes.contains(&c) {
+            classes.push(c);
+        }
+    }
+    if classes.len() == len {
+        return None;
+    }
+    Some(merged_value(classes.join(" "), pre_escaped))
+}
+
+/// Name for a prefixed attribute like `data-*` or `aria-*`
+fn prefixed_attr_name(prefix: &str, suffix: &str) -> CowStr {
+    debug_assert!(
+        is_valid_attr_name(suffix) && !suffix.chars().any(|c| c.is_ascii_uppercase()),
+        "invalid `{}*` attribute name suffix: {:?}",
+        prefix,
+        suffix
+    );
+    format!("{}{}", prefix, suffix).into()
+}
+
+/// A set of `data-*` attributes
+///
+/// Implemented for anything iterable over `(suffix, value)` pairs
+/// (`HashMap`, `BTreeMap`, `Vec`, arrays...). Implement it for
+/// your own types to attach their fields as `data-*` attributes.
+pub trait DataAttrs {
+    /// List of `(suffix, value)` pairs
+    fn data_attrs(self) -> Vec<(String, AttrValue)>;
+}
+
+impl<I, K, V> DataAttrs for I
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: Into<AttrValue>,
+{
+    fn data_attrs(self) -> Vec<(String, AttrValue)> {
+        self.into_iter()
+            .map(|(k, v)| (k.as_ref().to_owned(), v.into()))
+            .collect()
+    }
+}
+
+/// Kind of an element, deciding how it's rendered
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Element with content and an end tag
+    Normal,
+    /// Element that has no content and no end tag, eg. `<br>`
+    Void,
+    /// Element with text content that is not parsed as markup:
+    /// `<script>` and `<style>`
+    RawText,
+    /// Element with text content that can contain character
+    /// references, but no other markup: `<title>` and `<textarea>`
+    EscapableRawText,
+}
+
+/// HTML element
+///
+/// Implemented by marker types in `el`.
+pub trait Element {
+    /// Tag name
+    const NAME: &'static str;
+    /// Kind of the element
+    const KIND: Kind;
+}
+
+/// Element that can have content
+///
+/// Not implemented for void elements, so trying to pass
+/// children to them results in a compilation error.
+pub trait NonVoid: Element {}
+
+pub struct BareTag<E> {
+    element: PhantomData<E>,
+}
+
+impl<E> BareTag<E> {
+    /// Create a tag for an element
+    ///
+    /// Useful for custom elements with `Element` implemented by hand.
+    pub const fn new() -> Self {
+        BareTag {
+            element: PhantomData,
+        }
+    }
+}
+
+impl<E> Default for BareTag<E> {
+    fn default() -> Self {
+        BareTag::new()
+    }
+}
+
+pub struct Tag<E> {
+    attrs: Attrs,
+    element: PhantomData<E>,
+}
+
+pub struct FinalTag<E, I> {
+    attrs: Attrs,
+    inn: I,
+    element: PhantomData<E>,
+}
+
+fn render_open_tag(
+    tag: &str,
+    attrs: &[(CowStr, Option<AttrValue>)],
+    r: &mut dyn Renderer,
+) -> io::Result<()> {
+    if let Some((k, _)) = attrs.iter().find(|&(k, _)| !is_valid_attr_name(k)) {
+        return Err(Error::InvalidAttribute(InvalidAttrName(k.clone())).into());
+    }
+    r.write_raw_str("<")?;
+    r.write_raw_str(tag)?;
+    for (k, v) in attrs.iter() {
+        r.write_raw_str(" ")?;
+        r.write_raw_str(k)?;
+        if let Some(ref v) = *v {
+            r.write_raw_str("=\"")?;
+            v.render(attr_context(k), r)?;
+            r.write_raw_str("\"")?;
+        }
+    }
+    r.write_raw_str(">")
+}
+
+fn render_close_tag(tag: &str, r: &mut dyn Renderer) -> io::Result<()> {
+    r.write_raw_str("</")?;
+    r.write_raw_str(tag)?;
+    r.write_raw_str(">")
+}
+
+impl<E: Element> Render for Tag<E> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        render_open_tag(E::NAME, &self.attrs, r)?;
+        if E::KIND == Kind::Void {
+            return Ok(());
+        }
+        render_close_tag(E::NAME, r)
+    }
+}
+
+impl<E: Element> Render for BareTag<E> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        render_open_tag(E::NAME, &[], r)?;
+        if E::KIND == Kind::Void {
+            return Ok(());
+        }
+        render_close_tag(E::NAME, r)
+    }
+}
+
+impl<E: NonVoid, I: Render> Render for FinalTag<E, I> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        render_open_tag(E::NAME, &self.attrs, r)?;
+        match E::KIND {
+            Kind::RawText => {
+                let mut r = RawTextRenderer::new(r, content_context(E::NAME));
+                self.inn.render(&mut r)?;
+                r.flush()?;
+            }
+            _ => self.inn.render(r)?,
+        }
+        render_close_tag(E::NAME, r)
+    }
+}
+
+impl<E: Element> Tag<E> {
+    /// Set attribute
+    ///
+    /// Setting an attribute that is already set overrides its value,
+    /// except for `class` and `style` which are merged.
+    ///
+    /// A `None` value removes the attribute (`class` and `style` are
+    /// left as they are).
+    ///
+    /// In debug builds panics if `key` is not a valid attribute name.
+    /// Otherwise rendering the tag will fail with `InvalidAttrName`.
+    pub fn attr<K: Into<CowStr>, V: IntoAttrValue>(mut self, key: K, val: V) -> Tag<E> {
+        let key = key.into();
+        debug_assert!(
+            is_valid_attr_name(&key),
+            "invalid attribute name: {:?}",
+            key
+        );
+        let is_merged = key.eq_ignore_ascii_case("class") || key.eq_ignore_ascii_case("style");
+        let val = match val.into_attr_value() {
+            Some(val) => val,
+            None if is_merged => return self,
+            None => return self.remove_attr(&key),
+        };
+        if key.eq_ignore_ascii_case("class") {
+            return self.add_class(&val);
+        }
+        let val = match self.attr_pos(&key) {
+            Some(pos) if key.eq_ignore_ascii_case("style") => match self.attrs[pos].1 {
+                Some(ref old) => merge_style(old, val),
+                None => val,
+            },
+            _ => val,
+        };
+        self.set_attr(key, Some(val));
+        self
+    }
+    /// Set boolean attribute (with no value)
+    ///
+    /// See `attr` for handling of invalid names.
+    pub fn attr1<K: Into<CowStr>>(mut self, key: K) -> Tag<E> {
+        let key = key.into();
+        debug_assert!(
+            is_valid_attr_name(&key),
+            "invalid attribute name: {:?}",
+            key
+        );
+        self.set_attr(key, None);
+        self
+    }
+    /// Remove attribute
+    pub fn remove_attr(mut self, key: &str) -> Tag<E> {
+        if let Some(pos) = self.attr_pos(key) {
+            self.attrs.remove(pos);
+        }
+        self
+    }
+    /// Is attribute set
+    pub fn has_attr(&self, key: &str) -> bool {
+        self.attr_pos(key).is_some()
+    }
+    /// Value of an attribute
+    ///
+    /// `None` if the attribute is not set, or is a boolean attribute.
+    pub fn get_attr(&self, key: &str) -> Option<&AttrValue> {
+        self.attr_pos(key)
+            .and_then(|pos| self.attrs[pos].1.as_ref())
+    }
+    /// All the attributes, in order
+    pub fn attrs(&self) -> impl Iterator<Item = (&str, Option<&AttrValue>)> {
+        self.attrs.iter().map(|(k, v)| (&**k, v.as_ref()))
+    }
+    fn attr_pos(&self, key: &str) -> Option<usize> {
+        self.attrs
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case(key))
+    }
+    fn set_attr(&mut self, key: CowStr, val: Option<AttrValue>) {
+        match self.attr_pos(&key) {
+            Some(pos) => self.attrs[pos].1 = val,
+            None => self.attrs.push((key, val)),
+        }
+    }
+    /// Add attribute, checking if `key` is a valid attribute name
+    pub fn try_attr<K: Into<CowStr>, V: IntoAttrValue>(
+        self,
+        key: K,
+        val: V,
+    ) -> Result<Tag<E>, InvalidAttrName> {
+        let key = check_attr_name(key.into())?;
+        Ok(self.attr(key, val))
+    }
+    /// Add boolean attribute, checking if `key` is a valid attribute name
+    pub fn try_attr1<K: Into<CowStr>>(self, key: K) -> Result<Tag<E>, InvalidAttrName> {
+        let key = check_attr_name(key.into())?;
+        Ok(self.attr1(key))
+    }
+    /// Add `data-*` attribute
+    ///
+    /// In debug builds panics if `suffix` is not a valid `data-*`
+    /// attribute name suffix.
+    pub fn data<K: AsRef<str>, V: IntoAttrValue>(self, suffix: K, val: V) -> Tag<E> {
+        self.attr(prefixed_attr_name("data-", suffix.as_ref()), val)
+    }
+    /// Add all the `data-*` attributes from `attrs`
+    ///
+    /// See `data` for handling of invalid names.
+    pub fn data_attrs<D: DataAttrs>(self, attrs: D) -> Tag<E> {
+        attrs
+            .data_attrs()
+            .into_iter()
+            .fold(self, |tag, (k, v)| tag.data(k, v))
+    }
+    /// Add `aria-*` attribute
+    ///
+    /// See `data` for handling of invalid names.
+    pub fn aria<K: AsRef<str>, V: IntoAttrValue>(self, suffix: K, val: V) -> Tag<E> {
+        self.attr(prefixed_attr_name("aria-", suffix.as_ref()), val)
+    }
+    /// Add space separated class(es)
+    ///
+    /// All classes end up in a single `class` attribute, in the order
+    /// they were added, skipping duplicates.
+    pub fn class<C: AsRef<str>>(self, class: C) -> Tag<E> {
+        self.add_class(&AttrValue::Text(class.as_ref().to_owned().into()))
+    }
+    fn add_class(mut self, class: &AttrValue) -> Tag<E> {
+        if let Some(val) = merge_class(self.get_attr("class"), class) {
+            self.set_attr("class".into(), Some(val));
+        }
+        self
+    }
+    /// Add class(es) if `cond` is true
+    pub fn class_if<C: AsRef<str>>(self, cond: bool, class: C) -> Tag<E> {
+        if cond {
+            self.class(class)
+        } else {
+            self
+        }
+    }
+    /// Add all the classes from `classes`
+    pub fn classes<I, C>(self, classes: I) -> Tag<E>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<str>,
+    {
+        classes.into_iter().fold(self, |tag, c| tag.class(c))
+    }
+}
+
+impl<E: Element> BareTag<E> {
+    pub fn attr<K: Into<CowStr>, V: IntoAttrValue>(self, key: K, val: V) -> Tag<E> {
+        Tag::from(self).attr(key, val)
+    }
+    pub fn attr1<K: Into<CowStr>>(self, key: K) -> Tag<E> {
+        Tag::from(self).attr1(key)
+    }
+    pub fn try_attr<K: Into<CowStr>, V: IntoAttrValue>(
+        self,
+        key: K,
+        val: V,
+    ) -> Result<Tag<E>, InvalidAttrName> {
+        Tag::from(self).try_attr(key, val)
+    }
+    pub fn try_attr1<K: Into<CowStr>>(self, key: K) -> Result<Tag<E>, InvalidAttrName> {
+        Tag::from(self).try_attr1(key)
+    }
+    pub fn data<K: AsRef<str>, V: IntoAttrValue>(self, suffix: K, val: V) -> Tag<E> {
+        Tag::from(self).data(suffix, val)
+    }
+    pub fn data_attrs<D: DataAttrs>(self, attrs: D) -> Tag<E> {
+        Tag::from(self).data_attrs(attrs)
+    }
+    pub fn aria<K: AsRef<str>, V: IntoAttrValue>(self, suffix: K, val: V) -> Tag<E> {
+        Tag::from(self).aria(suffix, val)
+    }
+    pub fn class<C: AsRef<str>>(self, class: C) -> Tag<E> {
+        Tag::from(self).class(class)
+    }
+    pub fn class_if<C: AsRef<str>>(self, cond: bool, class: C) -> Tag<E> {
+        Tag::from(self).class_if(cond, class)
+    }
+    pub fn classes<I, C>(self, classes: I) -> Tag<E>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<str>,
+    {
+        Tag::from(self).classes(classes)
+    }
+}
+
+impl<E> From<BareTag<E>> for Tag<E> {
+    fn from(_: BareTag<E>) -> Self {
+        Tag {
+            attrs: vec![],
+            element: PhantomData,
+        }
+    }
+}
+
+impl<E: NonVoid> Tag<E> {
+    /// Set the content of the tag
+    ///
+    /// With the `nightly` feature, `tag(children)` does the same.
+    pub fn with<A: Render>(self, children: A) -> FinalTag<E, A> {
+        FinalTag {
+            attrs: self.attrs,
+            inn: children,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<E: NonVoid> BareTag<E> {
+    /// Set the content of the tag
+    ///
+    /// With the `nightly` feature, `tag(children)` does the same.
+    pub fn with<A: Render>(self, children: A) -> FinalTag<E, A> {
+        Tag::from(self).with(children)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<E: NonVoid, A: Render> FnOnce<(A,)> for Tag<E> {
+    type Output = FinalTag<E, A>;
+    extern "rust-call" fn call_once(self, args: (A,)) -> Self::Output {
+        self.with(args.0)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<E: NonVoid, A: Render> FnOnce<(A,)> for BareTag<E> {
+    type Output = FinalTag<E, A>;
+    extern "rust-call" fn call_once(self, args: (A,)) -> Self::Output {
+        self.with(args.0)
+    }
+}
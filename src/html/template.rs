@@ -0,0 +1,74 @@
+use std::borrow::Cow;
+use std::io;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use {Render, Renderer};
+
+type CowStr = Cow<'static, str>;
+
+/// Function rendering a template
+///
+/// Implemented for all functions taking `&Data` and returning
+/// `impl Render`, including ones borrowing from the data.
+pub trait TemplateFn<'a, D: 'a> {
+    type Output: Render + 'a;
+
+    fn call(&self, data: &'a D) -> Self::Output;
+}
+
+impl<'a, D: 'a, F, R> TemplateFn<'a, D> for F
+where
+    F: Fn(&'a D) -> R,
+    R: Render + 'a,
+{
+    type Output = R;
+
+    fn call(&self, data: &'a D) -> R {
+        self(data)
+    }
+}
+
+/// Named template made of a function
+///
+/// ```ignore
+/// pub fn home_tpl() -> impl stpl::Template {
+///     html::Template::new("home", templates::home::page)
+/// }
+/// ```
+pub struct Template<D, F> {
+    name: CowStr,
+    f: F,
+    data: PhantomData<fn(&D)>,
+}
+
+impl<D, F> Template<D, F>
+where
+    F: for<'a> TemplateFn<'a, D>,
+{
+    pub fn new<N: Into<CowStr>>(name: N, f: F) -> Self {
+        Template {
+            name: name.into(),
+            f,
+            data: PhantomData,
+        }
+    }
+}
+
+impl<D, F> ::Template for Template<D, F>
+where
+    D: Serialize + DeserializeOwned,
+    F: for<'a> TemplateFn<'a, D>,
+{
+    type Data = D;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, data: &D, r: &mut dyn Renderer) -> io::Result<()> {
+        self.f.call(data).render(r)
+    }
+}
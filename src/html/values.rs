@@ -0,0 +1,177 @@
+use super::AttrValue;
+
+/// Implement an enum of the keywords an attribute accepts
+macro_rules! impl_values {
+    ($(#[$m:meta])* $t:ident { $($v:ident => $s:expr,)* }) => {
+        $(#[$m])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub enum $t {
+            $($v,)*
+        }
+
+        impl $t {
+            /// Keyword as it appears in the markup
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    $($t::$v => $s,)*
+                }
+            }
+        }
+
+        impl From<$t> for AttrValue {
+            fn from(v: $t) -> Self {
+                AttrValue::Text(v.as_str().into())
+            }
+        }
+    };
+}
+
+impl_values! {
+    /// Value of `input`'s `type`
+    InputType {
+        Hidden => "hidden",
+        Text => "text",
+        Search => "search",
+        Tel => "tel",
+        Url => "url",
+        Email => "email",
+        Password => "password",
+        Date => "date",
+        Month => "month",
+        Week => "week",
+        Time => "time",
+        DatetimeLocal => "datetime-local",
+        Number => "number",
+        Range => "range",
+        Color => "color",
+        Checkbox => "checkbox",
+        Radio => "radio",
+        File => "file",
+        Submit => "submit",
+        Image => "image",
+        Reset => "reset",
+        Button => "button",
+    }
+}
+
+impl_values! {
+    /// Value of `button`'s `type`
+    ButtonType {
+        Submit => "submit",
+        Reset => "reset",
+        Button => "button",
+    }
+}
+
+impl_values! {
+    /// Value of `method` and `formmethod`
+    Method {
+        Get => "get",
+        Post => "post",
+        Dialog => "dialog",
+    }
+}
+
+impl_values! {
+    /// Value of `enctype` and `formenctype`
+    Enctype {
+        UrlEncoded => "application/x-www-form-urlencoded",
+        Multipart => "multipart/form-data",
+        Plain => "text/plain",
+    }
+}
+
+impl_values! {
+    /// Value of `crossorigin`
+    CrossOrigin {
+        Anonymous => "anonymous",
+        UseCredentials => "use-credentials",
+    }
+}
+
+impl_values! {
+    /// Value of `dir`
+    Dir {
+        Ltr => "ltr",
+        Rtl => "rtl",
+        Auto => "auto",
+    }
+}
+
+impl_values! {
+    /// Value of `loading`
+    Loading {
+        Eager => "eager",
+        Lazy => "lazy",
+    }
+}
+
+impl_values! {
+    /// Value of `decoding`
+    Decoding {
+        Sync => "sync",
+        Async => "async",
+        Auto => "auto",
+    }
+}
+
+impl_values! {
+    /// Value of `fetchpriority`
+    FetchPriority {
+        High => "high",
+        Low => "low",
+        Auto => "auto",
+    }
+}
+
+impl_values! {
+    /// Value of `preload`
+    Preload {
+        None => "none",
+        Metadata => "metadata",
+        Auto => "auto",
+    }
+}
+
+impl_values! {
+    /// Value of `referrerpolicy`
+    ReferrerPolicy {
+        NoReferrer => "no-referrer",
+        NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+        SameOrigin => "same-origin",
+        Origin => "origin",
+        StrictOrigin => "strict-origin",
+        OriginWhenCrossOrigin => "origin-when-cross-origin",
+        StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+        UnsafeUrl => "unsafe-url",
+    }
+}
+
+impl_values! {
+    /// Value of `th`'s `scope`
+    Scope {
+        Row => "row",
+        Col => "col",
+        Rowgroup => "rowgroup",
+        Colgroup => "colgroup",
+    }
+}
+
+impl_values! {
+    /// Value of `textarea`'s `wrap`
+    Wrap {
+        Soft => "soft",
+        Hard => "hard",
+    }
+}
+
+impl_values! {
+    /// Value of `track`'s `kind`
+    TrackKind {
+        Subtitles => "subtitles",
+        Captions => "captions",
+        Descriptions => "descriptions",
+        Chapters => "chapters",
+        Metadata => "metadata",
+    }
+}
@@ -0,0 +1,146 @@
+use std::io;
+
+use {Render, Renderer};
+
+/// Use to wrap iterators with
+///
+/// Renders every item. The iterator is cloned on each render, so
+/// it can be rendered more than once.
+pub struct Iter<I>(pub I);
+
+impl<I> Render for Iter<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Render,
+{
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        for t in self.0.clone() {
+            t.render(r)?;
+        }
+        Ok(())
+    }
+}
+
+/// Position of an item in the list being rendered by `each_pos`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// Index of the item, starting at 0
+    pub index: usize,
+    /// Is it the first item
+    pub first: bool,
+    /// Is it the last item
+    pub last: bool,
+}
+
+/// Render every item of `iter` with `f`
+///
+/// ```ignore
+/// ul(each(&data.items, |item| li(item.name.as_str())))
+/// ```
+///
+/// Items are rendered lazily, without collecting them first.
+pub fn each<I, F, R>(iter: I, f: F) -> Each<I, F>
+where
+    I: IntoIterator + Clone,
+    F: Fn(I::Item) -> R,
+    R: Render,
+{
+    Each { iter, f }
+}
+
+/// Like `each`, but `f` also gets the `Position` of the item
+///
+/// ```ignore
+/// each_pos(&data.items, |item, pos| li.class_if(pos.last, "last").with(item.as_str()))
+/// ```
+pub fn each_pos<I, F, R>(iter: I, f: F) -> EachPos<I, F>
+where
+    I: IntoIterator + Clone,
+    F: Fn(I::Item, Position) -> R,
+    R: Render,
+{
+    EachPos { iter, f }
+}
+
+/// Render all `items`, with `sep` between them
+///
+/// ```ignore
+/// join(&data.tags, ", ")
+/// ```
+pub fn join<I, S>(items: I, sep: S) -> Join<I, S>
+where
+    I: IntoIterator + Clone,
+    I::Item: Render,
+    S: Render,
+{
+    Join { items, sep }
+}
+
+/// See `each`
+pub struct Each<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F, R> Render for Each<I, F>
+where
+    I: IntoIterator + Clone,
+    F: Fn(I::Item) -> R,
+    R: Render,
+{
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        for item in self.iter.clone() {
+            (self.f)(item).render(r)?;
+        }
+        Ok(())
+    }
+}
+
+/// See `each_pos`
+pub struct EachPos<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F, R> Render for EachPos<I, F>
+where
+    I: IntoIterator + Clone,
+    F: Fn(I::Item, Position) -> R,
+    R: Render,
+{
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        let mut iter = self.iter.clone().into_iter().enumerate().peekable();
+        while let Some((index, item)) = iter.next() {
+            let pos = Position {
+                index,
+                first: index == 0,
+                last: iter.peek().is_none(),
+            };
+            (self.f)(item, pos).render(r)?;
+        }
+        Ok(())
+    }
+}
+
+/// See `join`
+pub struct Join<I, S> {
+    items: I,
+    sep: S,
+}
+
+impl<I, S> Render for Join<I, S>
+where
+    I: IntoIterator + Clone,
+    I::Item: Render,
+    S: Render,
+{
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        for (index, item) in self.items.clone().into_iter().enumerate() {
+            if index > 0 {
+                self.sep.render(r)?;
+            }
+            item.render(r)?;
+        }
+        Ok(())
+    }
+}
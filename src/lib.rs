@@ -13,7 +13,7 @@
 //! based-skeleton template for a Bootstrap-based UI.
 //!
 //! ```ignore
-//! pub fn base<C: Render + 'static>(data: &Data, content: C) -> impl Render {
+//! pub fn base<'a, C: Render + 'a>(data: &'a Data, content: C) -> impl Render + 'a {
 //!     (
 //!         doctype("html"),
 //!         html((
@@ -22,7 +22,7 @@
 //!                 meta.name("viewport").content("width=device-width, initial-scale=1, shrink-to-fit=no"),
 //!                 meta.name("description").content(""),
 //!                 meta.name("author").content("Dawid Ciężarkiewicz"),
-//!                 title(data.title.clone()),
+//!                 title(data.title.as_str()),
 //!                 (
 //!                     link.rel("icon").href("/static/favicon.ico"),
 //!                     link.rel("stylesheet").href("/static/theme/flatly/bootstrap.min.css"),
@@ -38,13 +38,13 @@
 //!                     content,
 //!                 ),
 //!                 (
-//!                 script.src("https://code.jquery.com/jquery-3.2.1.min.js").crossorigin("anonymous"),
+//!                 script.src("https://code.jquery.com/jquery-3.2.1.min.js").crossorigin(CrossOrigin::Anonymous),
 //!                 script.src("https://cdnjs.cloudflare.com/ajax/libs/popper.js/1.12.3/umd/popper.min.js")
 //!                     .integrity("sha384-vFJXuSJphROIrBnz7yo7oB41mKfc8JzQZiCq4NCceLEaO4IHwicKwpJf9c9IpFgh")
-//!                     .crossorigin("anonymous"),
+//!                     .crossorigin(CrossOrigin::Anonymous),
 //!                 script.src("https://maxcdn.bootstrapcdn.com/bootstrap/4.0.0-beta.2/js/bootstrap.min.js")
 //!                     .integrity("sha384-alpBpkh1PFOepccYVYDB4do5UnbKysX5WZXm3XxPqe5iKTfUKjNkCk9SaVuEZflJ")
-//!                     .crossorigin("anonymous"),
+//!                     .crossorigin(CrossOrigin::Anonymous),
 //!                 script.type_("text/javascript")(
 //!                     raw(include_str!("white-icon.js"))
 //!                 ),
@@ -60,9 +60,12 @@
 //!
 //! The function accepts arguments:
 //!
-//! * `data: Data` containing information how to "fill the blanks", and
+//! * `data: &Data` containing information how to "fill the blanks", and
 //! * `content: Render` - sub-template value that will be used as main page content.
 //!
+//! Tags accept borrowed children, so the returned template can borrow
+//! straight from `data` instead of cloning it.
+//!
 //! The function returns `Render` value that can be rendered as a string or bytes, or
 //! composed with other templates. The value is basically a one big tuple
 //! nesting many other `Render` values. `Render` is implemented for many standard types,
@@ -85,22 +88,101 @@
 //!
 //! ## Cons
 //!
-//! * `nightly`-only: This library relies on some unstable features:
-//!    * `#![feature(unboxed_closures)]`
-//!    # `![feature(fn_traits)]`
+//! * the `tag(children)` call syntax used in the examples needs `nightly`
+//!   Rust and the `nightly` cargo feature, as it relies on
+//!   `#![feature(unboxed_closures)]` and `#![feature(fn_traits)]`.
+//!   On stable Rust use `tag.with(children)` instead.
 //! * immature and incomplete: This library is still work in progress, and will
 //!   mature with time.
 //!
+//! ## Dynamic rendering
+//!
+//! Templates are compiled into the binary, but changing one doesn't
+//! require restarting the program using it. Wrap a template function in `html::Template`, call
+//! `handle_dynamic()` with it at the beginning of `main`, and render
+//! it with `render_dynamic_self`: the data is serialized and handed to a
+//! fresh run of the binary, so after a `cargo build` the new version of
+//! the template is used. In development, `dev::Watcher` can also run
+//! `cargo build` whenever template sources change. See `playground` for
+//! a complete example.
+//!
+//! Starting a process for every render takes milliseconds. For faster
+//! renders, build the templates into a `cdylib` exporting them with
+//! `export_templates!`, and load it with `hot::Library` (behind the
+//! `hot-reload` feature), calling `reload` after it's rebuilt. Both
+//! are `unsafe`: the library's code is run and trusted to match
+//! what `export_templates!` generates.
+//!
+//! Data is passed to the other binary with `transport`, which encodes it
+//! with a `Codec` (`transport::Bincode` by default, `transport::Json` for
+//! debugging) and checks that its type didn't change shape in the
+//! meantime, so data from a stale binary is an error rather than garbage.
+//!
 //! # Where to start
 //!
 //! You are most probably interested in reading `html` module documentation
-#![feature(unboxed_closures)]
-#![feature(fn_traits)]
+#![cfg_attr(feature = "nightly", feature(unboxed_closures, fn_traits))]
+use std::borrow::Cow;
+use std::collections::{BTreeSet, LinkedList, VecDeque};
 use std::fmt::Arguments;
-use std::{fmt, io};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fmt, io, net};
+
+extern crate bincode;
+#[cfg(feature = "hot-reload")]
+extern crate libloading;
+extern crate serde;
+extern crate serde_json;
 
+/// Development mode: rebuilding templates on changes
+pub mod dev;
+mod dynamic;
+mod error;
+/// Hot reloading templates compiled into a `cdylib`
+pub mod hot;
 /// HTML rendering
 pub mod html;
+mod iter;
+/// Number formatting
+pub mod num;
+mod registry;
+/// Serialization of template data passed to another binary
+pub mod transport;
+
+pub use dynamic::{
+    handle_dynamic, render_dynamic, render_dynamic_self, render_dynamic_with, HandleDynamic,
+    Template,
+};
+pub use error::Error;
+pub use iter::{each, each_pos, join, Each, EachPos, Iter, Join, Position};
+pub use registry::Registry;
+
+/// Escaping context
+///
+/// Describes where in the output the data is going to land, so that
+/// the `Renderer` can pick the right escaping rules.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Context {
+    /// Normal text content
+    Text,
+    /// Quoted attribute value
+    Attr,
+    /// Quoted attribute value holding an URL (`href`, `src`, etc.)
+    UrlAttr,
+    /// Content of `<script>`, kept from closing it early
+    ///
+    /// Values placed inside JavaScript strings need `html::js_string`.
+    Script,
+    /// Content of `<style>`, kept from closing it early
+    ///
+    /// Values placed inside CSS strings need `html::css_string`.
+    Style,
+    /// Content of a comment
+    Comment,
+}
 
 /// Rendering logic responsible for string escaping and such.
 ///
@@ -119,6 +201,15 @@ pub trait Renderer {
         self.write(s.as_bytes())
     }
 
+    /// Context write: perform escaping suitable for `ctx`
+    fn write_ctx(&mut self, _ctx: Context, data: &[u8]) -> io::Result<()> {
+        self.write(data)
+    }
+    /// Context write for `&str`
+    fn write_ctx_str(&mut self, ctx: Context, s: &str) -> io::Result<()> {
+        self.write_ctx(ctx, s.as_bytes())
+    }
+
     /// Raw write: no escaping should be performed
     fn write_raw(&mut self, data: &[u8]) -> io::Result<()>;
 
@@ -133,10 +224,12 @@ pub trait Renderer {
     }
 }
 
-/// A `Renderer` that does not escape anything it renders
+/// A `Renderer` that does not escape the content it renders
 ///
 /// A `Renderer` that uses underlying Renderer to call
-/// only `raw` methods, and thus avoid escaping values.
+/// only `raw` methods for normal writes, and thus avoid escaping
+/// content. Context writes (eg. attribute values of nested tags)
+/// are still escaped.
 pub struct RawRenderer<'a, T: 'a + ?Sized>(&'a mut T);
 
 impl<'a, T: 'a + Renderer + ?Sized> Renderer for RawRenderer<'a, T> {
@@ -149,6 +242,52 @@ impl<'a, T: 'a + Renderer + ?Sized> Renderer for RawRenderer<'a, T> {
     fn write_str(&mut self, s: &str) -> io::Result<()> {
         self.0.write_raw_str(s)
     }
+    fn write_ctx(&mut self, ctx: Context, data: &[u8]) -> io::Result<()> {
+        self.0.write_ctx(ctx, data)
+    }
+    fn write_ctx_str(&mut self, ctx: Context, s: &str) -> io::Result<()> {
+        self.0.write_ctx_str(ctx, s)
+    }
+    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.0.write_raw(data)
+    }
+    fn write_raw_fmt(&mut self, fmt: &Arguments) -> io::Result<()> {
+        self.0.write_raw_fmt(fmt)
+    }
+    fn write_raw_str(&mut self, s: &str) -> io::Result<()> {
+        self.0.write_raw_str(s)
+    }
+}
+
+/// A `Renderer` that escapes everything it renders for a given `Context`
+///
+/// A `Renderer` that uses underlying Renderer to call
+/// `write_ctx` for all normal writes, so the content lands
+/// escaped for the `Context` it is placed in.
+pub struct ContextRenderer<'a, T: 'a + ?Sized>(&'a mut T, Context);
+
+impl<'a, T: 'a + Renderer + ?Sized> ContextRenderer<'a, T> {
+    pub fn new(r: &'a mut T, ctx: Context) -> Self {
+        ContextRenderer(r, ctx)
+    }
+}
+
+impl<'a, T: 'a + Renderer + ?Sized> Renderer for ContextRenderer<'a, T> {
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.0.write_ctx(self.1, data)
+    }
+    fn write_fmt(&mut self, fmt: &Arguments) -> io::Result<()> {
+        self.0.write_ctx(self.1, format!("{}", fmt).as_bytes())
+    }
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.0.write_ctx_str(self.1, s)
+    }
+    fn write_ctx(&mut self, ctx: Context, data: &[u8]) -> io::Result<()> {
+        self.0.write_ctx(ctx, data)
+    }
+    fn write_ctx_str(&mut self, ctx: Context, s: &str) -> io::Result<()> {
+        self.0.write_ctx_str(ctx, s)
+    }
     fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
         self.0.write_raw(data)
     }
@@ -171,13 +310,16 @@ impl<'a, T: 'a + Renderer + ?Sized> Renderer for RawRenderer<'a, T> {
 ///
 /// You can impl it for your own types too. You usually compose it
 /// from many other `impl Render` data.
+///
+/// All string types (`&str`, `String`, `Cow<str>`, `Rc<str>`...)
+/// are escaped when rendered. Use `html::PreEscaped` for trusted markup.
 pub trait Render {
-    fn render(&self, &mut Renderer) -> io::Result<()>;
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()>;
 }
 
 // {{{ impl Render
 impl<T: Render> Render for Vec<T> {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
         for t in self.iter() {
             t.render(r)?;
         }
@@ -186,7 +328,7 @@ impl<T: Render> Render for Vec<T> {
 }
 
 impl<T: Render> Render for [T] {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
         for t in self.iter() {
             t.render(r)?;
         }
@@ -194,10 +336,16 @@ impl<T: Render> Render for [T] {
     }
 }
 
-macro_rules! impl_narr {
-    ($n:expr) => {
-        impl<T: Render> Render for [T; $n] {
-            fn render(&self, r: &mut Renderer) -> io::Result<()> {
+impl<T: Render, const N: usize> Render for [T; N] {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        self[..].render(r)
+    }
+}
+
+macro_rules! impl_render_iter {
+    ($t:ident) => {
+        impl<T: Render> Render for $t<T> {
+            fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
                 for t in self.iter() {
                     t.render(r)?;
                 }
@@ -207,78 +355,78 @@ macro_rules! impl_narr {
     };
 }
 
-impl_narr!(0);
-impl_narr!(1);
-impl_narr!(2);
-impl_narr!(3);
-impl_narr!(4);
-impl_narr!(5);
-impl_narr!(6);
-impl_narr!(7);
-impl_narr!(8);
-impl_narr!(9);
-impl_narr!(10);
-impl_narr!(11);
-impl_narr!(12);
-impl_narr!(13);
-impl_narr!(14);
-impl_narr!(15);
-impl_narr!(16);
-impl_narr!(17);
-impl_narr!(18);
-impl_narr!(19);
-impl_narr!(20);
-impl_narr!(21);
-impl_narr!(22);
-impl_narr!(23);
-impl_narr!(24);
-impl_narr!(25);
-impl_narr!(26);
-impl_narr!(27);
-impl_narr!(28);
-impl_narr!(29);
-impl_narr!(30);
-impl_narr!(31);
-impl_narr!(32);
-
-impl<'a, T: Render + ?Sized> Render for &'a mut T {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
+impl_render_iter!(VecDeque);
+impl_render_iter!(LinkedList);
+impl_render_iter!(BTreeSet);
+
+impl<T: Render + ?Sized> Render for &T {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        (**self).render(r)
+    }
+}
+
+impl<T: Render + ?Sized> Render for &mut T {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
         (**self).render(r)?;
         Ok(())
     }
 }
 
 impl<T: Render + ?Sized> Render for Box<T> {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
         (**self).render(r)?;
         Ok(())
     }
 }
 
 impl Render for () {
-    fn render(&self, _: &mut Renderer) -> io::Result<()> {
+    fn render(&self, _: &mut dyn Renderer) -> io::Result<()> {
         Ok(())
     }
 }
 
 impl<R: Render> Render for Option<R> {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        if let &Some(ref s) = self {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        if let Some(s) = self {
             s.render(r)?
         }
         Ok(())
     }
 }
+impl Render for str {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_str(self)
+    }
+}
+
 impl Render for String {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        r.write_raw(self.as_bytes())
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_str(self)
+    }
+}
+
+impl<'a, T: Render + ToOwned + ?Sized> Render for Cow<'a, T> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        (**self).render(r)
+    }
+}
+
+impl<T: Render + ?Sized> Render for Rc<T> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        (**self).render(r)
+    }
+}
+
+impl<T: Render + ?Sized> Render for Arc<T> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        (**self).render(r)
     }
 }
 
 macro_rules! impl_render_raw {
     ($t:ty) => {
         impl Render for $t {
-            fn render(&self, r: &mut Renderer) -> io::Result<()> {
+            fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
                 r.write_raw_fmt(&format_args!("{}", self))
             }
         }
@@ -293,151 +441,152 @@ impl_render_raw!(i32);
 impl_render_raw!(u32);
 impl_render_raw!(usize);
 impl_render_raw!(isize);
+impl_render_raw!(i128);
+impl_render_raw!(u128);
+impl_render_raw!(i16);
+impl_render_raw!(u16);
+impl_render_raw!(i8);
+impl_render_raw!(u8);
+impl_render_raw!(std::num::NonZeroU8);
+impl_render_raw!(std::num::NonZeroU16);
+impl_render_raw!(std::num::NonZeroU32);
+impl_render_raw!(std::num::NonZeroU64);
+impl_render_raw!(std::num::NonZeroU128);
+impl_render_raw!(std::num::NonZeroUsize);
+impl_render_raw!(std::num::NonZeroI8);
+impl_render_raw!(std::num::NonZeroI16);
+impl_render_raw!(std::num::NonZeroI32);
+impl_render_raw!(std::num::NonZeroI64);
+impl_render_raw!(std::num::NonZeroI128);
+impl_render_raw!(std::num::NonZeroIsize);
 
-impl<'a> Render for &'a str {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        r.write_str(self)
-    }
+macro_rules! impl_render_display {
+    ($t:ty) => {
+        impl Render for $t {
+            fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+                r.write_fmt(&format_args!("{}", self))
+            }
+        }
+    };
 }
 
-impl<'a> Render for fmt::Arguments<'a> {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        r.write_fmt(self)
-    }
-}
+impl_render_display!(bool);
+impl_render_display!(char);
+impl_render_display!(net::IpAddr);
+impl_render_display!(net::Ipv4Addr);
+impl_render_display!(net::Ipv6Addr);
+impl_render_display!(net::SocketAddr);
 
-impl<'a> Render for &'a fmt::Arguments<'a> {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        r.write_fmt(self)
+/// Rendered in its `Debug` format, eg. `1.5s`
+impl Render for Duration {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_fmt(&format_args!("{:?}", self))
     }
 }
 
-impl<A> Render for (A,)
-where
-    A: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)
+/// Rendered lossily: invalid unicode is replaced with `U+FFFD`
+impl Render for Path {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_str(&self.to_string_lossy())
     }
 }
 
-impl<A, B> Render for (A, B)
-where
-    A: Render,
-    B: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)
+impl Render for PathBuf {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        self.as_path().render(r)
     }
 }
 
-impl<A, B, C> Render for (A, B, C)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)
-    }
-}
+/// Use to wrap any `fmt::Display` value with
+///
+/// The formatted value is escaped, just like strings.
+pub struct Display<T>(pub T);
 
-impl<A, B, C, D> Render for (A, B, C, D)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-    D: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)?;
-        self.3.render(r)
+impl<T: fmt::Display> Render for Display<T> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_fmt(&format_args!("{}", self.0))
     }
 }
-impl<A, B, C, D, E> Render for (A, B, C, D, E)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-    D: Render,
-    E: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)?;
-        self.3.render(r)?;
-        self.4.render(r)
+
+impl<'a> Render for fmt::Arguments<'a> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_fmt(self)
     }
 }
 
-impl<A, B, C, D, E, F> Render for (A, B, C, D, E, F)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-    D: Render,
-    E: Render,
-    F: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)?;
-        self.3.render(r)?;
-        self.4.render(r)?;
-        self.5.render(r)
+/// `Err` fails rendering with `Error::Custom` carrying its message
+impl<T: Render, E: fmt::Display> Render for Result<T, E> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        match *self {
+            Ok(ref t) => t.render(r),
+            Err(ref e) => Err(Error::custom(e.to_string()).into()),
+        }
     }
 }
 
-impl<A, B, C, D, E, F, G> Render for (A, B, C, D, E, F, G)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-    D: Render,
-    E: Render,
-    F: Render,
-    G: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)?;
-        self.3.render(r)?;
-        self.4.render(r)?;
-        self.5.render(r)?;
-        self.6.render(r)
-    }
+macro_rules! impl_ntuple {
+    ($($t:ident $n:tt),+) => {
+        impl<$($t: Render),+> Render for ($($t,)+) {
+            fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+                $(self.$n.render(r)?;)+
+                Ok(())
+            }
+        }
+    };
 }
 
-impl<A, B, C, D, E, F, G, H> Render for (A, B, C, D, E, F, G, H)
-where
-    A: Render,
-    B: Render,
-    C: Render,
-    D: Render,
-    E: Render,
-    F: Render,
-    G: Render,
-    H: Render,
-{
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
-        self.0.render(r)?;
-        self.1.render(r)?;
-        self.2.render(r)?;
-        self.3.render(r)?;
-        self.4.render(r)?;
-        self.5.render(r)?;
-        self.6.render(r)?;
-        self.7.render(r)?;
-        Ok(())
-    }
+impl_ntuple!(T0 0);
+impl_ntuple!(T0 0, T1 1);
+impl_ntuple!(T0 0, T1 1, T2 2);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26, T27 27);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26, T27 27, T28 28);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26, T27 27, T28 28, T29 29);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26, T27 27, T28 28, T29 29, T30 30);
+impl_ntuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12, T13 13, T14 14, T15 15, T16 16, T17 17, T18 18, T19 19, T20 20, T21 21, T22 22, T23 23, T24 24, T25 25, T26 26, T27 27, T28 28, T29 29, T30 30, T31 31);
+
+/// Combine any number of `Render` values into one
+///
+/// ```ignore
+/// fragment!(h1("Title"), p("First"), p("Second"), footer)
+/// ```
+///
+/// Unlike tuples, the number of elements is not limited to 32: they are
+/// grouped in tuples of up to 16, nested as `((a, b, ...), rest)`. Each
+/// group is a level of macro recursion, so the compiler's default
+/// recursion limit allows about two thousand elements.
+#[macro_export]
+macro_rules! fragment {
+    () => { () };
+    ($e0:expr, $e1:expr, $e2:expr, $e3:expr, $e4:expr, $e5:expr, $e6:expr, $e7:expr, $e8:expr, $e9:expr, $e10:expr, $e11:expr, $e12:expr, $e13:expr, $e14:expr, $e15:expr, $($rest:expr),+ $(,)*) => {
+        (($e0, $e1, $e2, $e3, $e4, $e5, $e6, $e7, $e8, $e9, $e10, $e11, $e12, $e13, $e14, $e15), $crate::fragment!($($rest),+))
+    };
+    ($($e:expr),+ $(,)*) => {
+        ($($e,)+)
+    };
 }
 
 /// Use to wrap closures with
@@ -445,12 +594,31 @@ pub struct Fn<F>(pub F);
 
 impl<F> Render for Fn<F>
 where
-    F: std::ops::Fn(&mut Renderer) -> io::Result<()>,
+    F: std::ops::Fn(&mut dyn Renderer) -> io::Result<()>,
 {
-    fn render(&self, r: &mut Renderer) -> io::Result<()> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
         self.0(r)
     }
 }
+
+/// Use to wrap closures that can fail with a template-level `Error`
+///
+/// ```ignore
+/// TryFn(move |r: &mut dyn Renderer| match user {
+///     Some(ref user) => Ok(user.name.render(r)?),
+///     None => Err(Error::custom("no user")),
+/// })
+/// ```
+pub struct TryFn<F>(pub F);
+
+impl<F> Render for TryFn<F>
+where
+    F: std::ops::Fn(&mut dyn Renderer) -> Result<(), Error>,
+{
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        self.0(r).map_err(Into::into)
+    }
+}
 // }}}
 //
 // vim: foldmethod=marker foldmarker={{{,}}}
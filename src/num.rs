@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+
+use {Render, Renderer};
+
+type CowStr = Cow<'static, str>;
+
+/// Suffixes used by `compact`, with their scale
+const COMPACT_UNITS: &[(f64, &str)] = &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k")];
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Numeric type that can be formatted with `Format`
+///
+/// Implemented for the primitive integer and floating point types
+/// (and references to them) only, as the formatting relies on how
+/// they are displayed; precision would truncate a string instead of
+/// rounding it.
+pub trait Num: fmt::Display + sealed::Sealed {}
+
+macro_rules! impl_num {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl Num for $t {}
+        )*
+    };
+}
+
+impl_num!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<N: Num> sealed::Sealed for &N {}
+impl<N: Num> Num for &N {}
+
+/// Number format: separators and precision
+///
+/// ```ignore
+/// let de = Format::new().thousands_sep(".").decimal_sep(",");
+/// td(de.currency(1234.5, "€ ")) // `€ 1.234,50`
+/// ```
+///
+/// The default is `1,234,567.89`, with the precision the number
+/// is displayed with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Format {
+    thousands_sep: CowStr,
+    decimal_sep: CowStr,
+    precision: Option<usize>,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            thousands_sep: ",".into(),
+            decimal_sep: ".".into(),
+            precision: None,
+        }
+    }
+}
+
+impl Format {
+    pub fn new() -> Self {
+        Format::default()
+    }
+
+    /// Set the separator between groups of thousands (`""` to disable)
+    pub fn thousands_sep<S: Into<CowStr>>(mut self, sep: S) -> Self {
+        self.thousands_sep = sep.into();
+        self
+    }
+
+    /// Set the separator between integer and fractional part
+    pub fn decimal_sep<S: Into<CowStr>>(mut self, sep: S) -> Self {
+        self.decimal_sep = sep.into();
+        self
+    }
+
+    /// Round to `precision` decimal places
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Format `n`
+    pub fn number<N: Num>(&self, n: N) -> Number<N> {
+        Number {
+            value: n,
+            format: self.clone(),
+            prefix: "".into(),
+            suffix: "".into(),
+            trim_zeros: false,
+        }
+    }
+
+    /// Format `n` as an amount of money, with 2 decimal places
+    /// (unless set otherwise) and `symbol` in front
+    pub fn currency<N: Num, S: Into<CowStr>>(&self, n: N, symbol: S) -> Number<N> {
+        let mut num = self.number(n);
+        num.format.precision = Some(self.precision.unwrap_or(2));
+        num.prefix = symbol.into();
+        num
+    }
+
+    /// Format `ratio` as percent, eg. `0.25` as `25%`, with no decimal
+    /// places (unless set otherwise)
+    pub fn percent(&self, ratio: f64) -> Number<f64> {
+        let mut num = self.number(ratio * 100.0);
+        num.format.precision = Some(self.precision.unwrap_or(0));
+        num.suffix = "%".into();
+        num
+    }
+
+    /// Format `n` in a short form, eg. `1234` as `1.2k`, with up to
+    /// 1 decimal place (unless set otherwise)
+    pub fn compact(&self, n: f64) -> Number<f64> {
+        let precision = self.precision.unwrap_or(1);
+        let factor = 10f64.powi(precision as i32);
+        let unit = |pos: usize| COMPACT_UNITS.get(pos).map_or((1.0, ""), |&unit| unit);
+        // Pick the unit by magnitude, moving to the next one if rounding
+        // reaches 1000 of it, so that `999_999` is `1M`, not `1,000k`,
+        // while `950` stays `950`
+        let mut pos = COMPACT_UNITS
+            .iter()
+            .position(|&(scale, _)| n.abs() >= scale)
+            .unwrap_or(COMPACT_UNITS.len());
+        if pos > 0 && (n.abs() / unit(pos).0 * factor).round() / factor >= 1000.0 {
+            pos -= 1;
+        }
+        let (scale, unit) = unit(pos);
+        let mut num = self.number(n / scale);
+        num.format.precision = Some(precision);
+        num.suffix = unit.into();
+        num.trim_zeros = true;
+        num
+    }
+
+    /// Apply the format to `n` as displayed by Rust
+    fn apply(&self, n: &str, trim_zeros: bool) -> Option<(bool, String)> {
+        let (negative, n) = match n.strip_prefix('-') {
+            Some(n) => (true, n),
+            None => (false, n),
+        };
+        let (int, frac) = match n.find('.') {
+            Some(pos) => (&n[..pos], &n[pos + 1..]),
+            None => (n, ""),
+        };
+        if int.is_empty() || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+            // `inf`, `NaN` and the like
+            return None;
+        }
+        let mut frac = frac.to_owned();
+        if let Some(precision) = self.precision {
+            while frac.len() < precision {
+                frac.push('0');
+            }
+        }
+        if trim_zeros {
+            let len = frac.trim_end_matches('0').len();
+            frac.truncate(len);
+        }
+        let mut out = String::with_capacity(n.len() * 2);
+        for (pos, digit) in int.chars().enumerate() {
+            if pos > 0 && (int.len() - pos) % 3 == 0 {
+                out.push_str(&self.thousands_sep);
+            }
+            out.push(digit);
+        }
+        if !frac.is_empty() {
+            out.push_str(&self.decimal_sep);
+            out.push_str(&frac);
+        }
+        let is_zero = int.bytes().chain(frac.bytes()).all(|b| b == b'0');
+        Some((negative && !is_zero, out))
+    }
+}
+
+/// Number formatted with `Format`
+pub struct Number<N> {
+    value: N,
+    format: Format,
+    prefix: CowStr,
+    suffix: CowStr,
+    trim_zeros: bool,
+}
+
+impl<N: Num> fmt::Display for Number<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self.format.precision {
+            Some(precision) => format!("{:.*}", precision, self.value),
+            None => self.value.to_string(),
+        };
+        match self.format.apply(&s, self.trim_zeros) {
+            Some((negative, s)) => write!(
+                f,
+                "{}{}{}{}",
+                if negative { "-" } else { "" },
+                self.prefix,
+                s,
+                self.suffix
+            ),
+            None => write!(f, "{}", s),
+        }
+    }
+}
+
+impl<N: Num> Render for Number<N> {
+    fn render(&self, r: &mut dyn Renderer) -> io::Result<()> {
+        r.write_str(&self.to_string())
+    }
+}
+
+/// Format `n` with the default `Format`, eg. `1,234,567.89`
+pub fn number<N: Num>(n: N) -> Number<N> {
+    Format::new().number(n)
+}
+
+/// Format `n` with `precision` decimal places, eg. `1,234.50`
+pub fn fixed<N: Num>(n: N, precision: usize) -> Number<N> {
+    Format::new().precision(precision).number(n)
+}
+
+/// See `Format::currency`
+pub fn currency<N: Num, S: Into<CowStr>>(n: N, symbol: S) -> Number<N> {
+    Format::new().currency(n, symbol)
+}
+
+/// See `Format::percent`
+pub fn percent(ratio: f64) -> Number<f64> {
+    Format::new().percent(ratio)
+}
+
+/// See `Format::compact`
+pub fn compact(n: f64) -> Number<f64> {
+    Format::new().compact(n)
+}
@@ -0,0 +1,155 @@
+use std::any::{self, Any, TypeId};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Value};
+
+use html::{self, TemplateFn};
+use {Error, Render, Renderer, Template};
+
+type CowStr = Cow<'static, str>;
+
+/// Template with its data type erased
+trait AnyTemplate: Send + Sync {
+    fn data_type_id(&self) -> TypeId;
+    fn data_type(&self) -> &'static str;
+    fn render_any(&self, data: &dyn Any, r: &mut dyn Renderer) -> io::Result<()>;
+    fn render_value(&self, value: &Value, r: &mut dyn Renderer) -> Result<(), Error>;
+}
+
+impl<T> AnyTemplate for T
+where
+    T: Template + Send + Sync,
+    T::Data: Any,
+{
+    fn data_type_id(&self) -> TypeId {
+        TypeId::of::<T::Data>()
+    }
+
+    fn data_type(&self) -> &'static str {
+        any::type_name::<T::Data>()
+    }
+
+    fn render_any(&self, data: &dyn Any, r: &mut dyn Renderer) -> io::Result<()> {
+        let data = data
+            .downcast_ref::<T::Data>()
+            .expect("data type checked by `Registry::template`");
+        self.render(data, r)
+    }
+
+    fn render_value(&self, value: &Value, r: &mut dyn Renderer) -> Result<(), Error> {
+        let data: T::Data =
+            serde_json::from_value(value.clone()).map_err(|e| Error::Data(Box::new(e)))?;
+        self.render(&data, r)?;
+        Ok(())
+    }
+}
+
+/// Set of templates rendered by name
+///
+/// ```ignore
+/// let mut registry = Registry::new();
+/// registry
+///     .register("home", home::page)
+///     .register("about", about::page);
+///
+/// registry.render("home", &home_data, &mut out)?;
+/// ```
+///
+/// Data is type-checked when rendering: passing data of a type other
+/// than the template expects fails with `Error::WrongDataType`.
+///
+/// Templates must be `Send + Sync`, so the registry can be shared
+/// between threads.
+#[derive(Default)]
+pub struct Registry {
+    templates: BTreeMap<CowStr, Box<dyn AnyTemplate>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Register a template function under `name`
+    ///
+    /// Registering a name again replaces the previous template.
+    pub fn register<N, D, F>(&mut self, name: N, f: F) -> &mut Self
+    where
+        N: Into<CowStr>,
+        D: Any + Serialize + DeserializeOwned,
+        F: for<'a> TemplateFn<'a, D> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.templates
+            .insert(name.clone(), Box::new(html::Template::new(name, f)));
+        self
+    }
+
+    /// Register `template` under its own name
+    pub fn register_template<T>(&mut self, template: T) -> &mut Self
+    where
+        T: Template + Send + Sync + 'static,
+        T::Data: Any,
+    {
+        self.templates
+            .insert(template.name().to_owned().into(), Box::new(template));
+        self
+    }
+
+    /// Names of all the registered templates, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(|name| &**name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.templates.contains_key(name)
+    }
+
+    fn get(&self, name: &str) -> Result<&dyn AnyTemplate, Error> {
+        self.templates
+            .get(name)
+            .map(|template| &**template)
+            .ok_or_else(|| Error::UnknownTemplate(name.to_owned()))
+    }
+
+    /// Template `name` with `data`, ready to be rendered
+    pub fn template<'a, D: Any>(
+        &'a self,
+        name: &str,
+        data: &'a D,
+    ) -> Result<impl Render + 'a, Error> {
+        let template = self.get(name)?;
+        if template.data_type_id() != TypeId::of::<D>() {
+            return Err(Error::WrongDataType {
+                template: name.to_owned(),
+                expected: template.data_type(),
+            });
+        }
+        Ok(::Fn(move |r: &mut dyn Renderer| {
+            template.render_any(data, r)
+        }))
+    }
+
+    /// Render template `name` with `data`
+    pub fn render<D: Any>(&self, name: &str, data: &D, r: &mut dyn Renderer) -> Result<(), Error> {
+        self.template(name, data)?.render(r)?;
+        Ok(())
+    }
+
+    /// Render template `name` with data deserialized from `value`
+    ///
+    /// For cases where the data type is not known statically, eg. data
+    /// coming from a request or a file.
+    pub fn render_value(
+        &self,
+        name: &str,
+        value: &Value,
+        r: &mut dyn Renderer,
+    ) -> Result<(), Error> {
+        self.get(name)?.render_value(value, r)
+    }
+}
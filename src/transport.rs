@@ -0,0 +1,158 @@
+use std::any;
+
+use bincode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+
+use Error;
+
+mod shape;
+
+pub use self::shape::fingerprint;
+
+/// Version of the message format, bumped on incompatible changes
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Length of the header preceding the encoded data:
+/// format version, codec id and data fingerprint
+const HEADER_LEN: usize = 10;
+
+/// Serialization format of template data passed to another binary
+///
+/// `Json` is easy to inspect when debugging, `Bincode` is compact and
+/// fast. Other formats can be plugged in by implementing this trait
+/// with an `ID` not used by the built-in codecs; the receiving side
+/// has to be told about them, see `HandleDynamic::codec` and
+/// `export_templates!`.
+pub trait Codec {
+    /// Identifies the codec in the message header
+    const ID: u8;
+
+    fn encode<T: Serialize>(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error>;
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error>;
+}
+
+/// JSON codec, for debugging
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl Codec for Json {
+    const ID: u8 = 1;
+
+    fn encode<T: Serialize>(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        serde_json::to_writer(out, value).map_err(|e| Error::Data(Box::new(e)))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        serde_json::from_slice(bytes).map_err(|e| Error::Data(Box::new(e)))
+    }
+}
+
+/// Compact binary codec, the default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bincode;
+
+impl Codec for Bincode {
+    const ID: u8 = 2;
+
+    fn encode<T: Serialize>(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        bincode::serialize_into(out, value).map_err(|e| Error::Data(e))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        bincode::deserialize(bytes).map_err(|e| Error::Data(e))
+    }
+}
+
+/// Encode `value` with `codec`, behind a header for `decode`
+pub fn encode<C: Codec, T: Serialize>(codec: &C, value: &T) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(HEADER_LEN);
+    out.push(FORMAT_VERSION);
+    out.push(C::ID);
+    out.extend_from_slice(&fingerprint(value)?.to_le_bytes());
+    codec.encode(value, &mut out)?;
+    Ok(out)
+}
+
+/// How the receiving side decodes template data encoded by `encode`
+///
+/// Implemented by every `Codec`, accepting only data it encoded, and by
+/// `BuiltIn`, accepting data encoded with any of the built-in codecs.
+pub trait Decoder {
+    fn decode_message<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error>;
+}
+
+impl<C: Codec> Decoder for C {
+    fn decode_message<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        decode_with(self, bytes)
+    }
+}
+
+/// Decoder for data encoded with any of the built-in codecs, the
+/// default on the receiving side
+///
+/// See `decode`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BuiltIn;
+
+impl Decoder for BuiltIn {
+    fn decode_message<T: Serialize + DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        decode(bytes)
+    }
+}
+
+/// Decode a value encoded by `encode` with one of the built-in codecs
+///
+/// Besides the format version, the shape of the decoded value is
+/// compared with the one that was encoded, so a data type that changed
+/// between the two binaries is reported as `Error::DataShape` instead
+/// of rendering garbage.
+pub fn decode<T: Serialize + DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    match header(bytes)?.0 {
+        Json::ID => decode_with(&Json, bytes),
+        Bincode::ID => decode_with(&Bincode, bytes),
+        id => Err(Error::Transport(format!("unknown codec id {}", id))),
+    }
+}
+
+/// Decode a value encoded by `encode` with `codec`
+///
+/// See `decode`.
+pub fn decode_with<C: Codec, T: Serialize + DeserializeOwned>(
+    codec: &C,
+    bytes: &[u8],
+) -> Result<T, Error> {
+    let (id, expected) = header(bytes)?;
+    if id != C::ID {
+        return Err(Error::Transport(format!(
+            "data encoded with codec id {}, expected {}",
+            id,
+            C::ID
+        )));
+    }
+    let value: T = codec.decode(&bytes[HEADER_LEN..])?;
+    if fingerprint(&value)? != expected {
+        return Err(Error::DataShape {
+            data_type: any::type_name::<T>(),
+        });
+    }
+    Ok(value)
+}
+
+/// Codec id and fingerprint from the header of `bytes`
+fn header(bytes: &[u8]) -> Result<(u8, u64), Error> {
+    if bytes.len() < HEADER_LEN {
+        return Err(Error::Transport("data too short".into()));
+    }
+    if bytes[0] != FORMAT_VERSION {
+        return Err(Error::Transport(format!(
+            "data format version {}, expected {}",
+            bytes[0], FORMAT_VERSION
+        )));
+    }
+    let mut fingerprint = [0; 8];
+    fingerprint.copy_from_slice(&bytes[2..HEADER_LEN]);
+    Ok((bytes[1], u64::from_le_bytes(fingerprint)))
+}
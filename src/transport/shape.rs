@@ -0,0 +1,407 @@
+use std::{error, fmt};
+
+use serde::ser::{self, Serialize};
+
+use Error;
+
+/// Fingerprint of the shape of `value`
+///
+/// Covers type, struct, field and variant names and the kinds of
+/// primitive values, but not the values themselves. The same value
+/// always has the same fingerprint, so a value that changed shape
+/// after a round trip through a codec was decoded as a different
+/// data type than it was encoded with.
+///
+/// Sequence elements and map entries are combined in any order, so
+/// collections with unstable iteration order, like `HashSet` and
+/// `HashMap`, are fine.
+pub fn fingerprint<T: Serialize + ?Sized>(value: &T) -> Result<u64, Error> {
+    let mut shape = Shape::new();
+    value
+        .serialize(&mut shape)
+        .map_err(|e| Error::Data(Box::new(e)))?;
+    Ok(shape.hash)
+}
+
+/// Error returned by a `Serialize` implementation
+#[derive(Debug)]
+struct ShapeError(String);
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for ShapeError {}
+
+impl ser::Error for ShapeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ShapeError(msg.to_string())
+    }
+}
+
+/// 64-bit FNV-1a, which is stable across binaries, unlike
+/// `DefaultHasher`
+struct Shape {
+    hash: u64,
+}
+
+impl Shape {
+    fn new() -> Self {
+        Shape {
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.hash ^= u64::from(b);
+            self.hash = self.hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn tag(&mut self, tag: u8) -> Result<(), ShapeError> {
+        self.write(&[tag]);
+        Ok(())
+    }
+
+    fn name(&mut self, tag: u8, name: &str) -> Result<(), ShapeError> {
+        self.write(&[tag]);
+        self.write(&(name.len() as u64).to_le_bytes());
+        self.write(name.as_bytes());
+        Ok(())
+    }
+
+    fn len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+    type SerializeSeq = Unordered<'a>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Unordered<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _: bool) -> Result<(), ShapeError> {
+        self.tag(b'b')
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), ShapeError> {
+        self.tag(1)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), ShapeError> {
+        self.tag(2)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), ShapeError> {
+        self.tag(3)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), ShapeError> {
+        self.tag(4)
+    }
+
+    fn serialize_i128(self, _: i128) -> Result<(), ShapeError> {
+        self.tag(5)
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), ShapeError> {
+        self.tag(6)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), ShapeError> {
+        self.tag(7)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), ShapeError> {
+        self.tag(8)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), ShapeError> {
+        self.tag(9)
+    }
+
+    fn serialize_u128(self, _: u128) -> Result<(), ShapeError> {
+        self.tag(10)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), ShapeError> {
+        self.tag(b'f')
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), ShapeError> {
+        self.tag(b'F')
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), ShapeError> {
+        self.tag(b'c')
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), ShapeError> {
+        self.tag(b's')
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), ShapeError> {
+        self.tag(b'B')
+    }
+
+    fn serialize_none(self) -> Result<(), ShapeError> {
+        self.tag(b'n')
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ShapeError> {
+        self.tag(b'?')?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ShapeError> {
+        self.tag(b'u')
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), ShapeError> {
+        self.name(b'U', name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), ShapeError> {
+        self.name(b'e', name)?;
+        self.name(b'v', variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ShapeError> {
+        self.name(b'N', name)?;
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), ShapeError> {
+        self.name(b'e', name)?;
+        self.name(b'V', variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Unordered<'a>, ShapeError> {
+        Unordered::new(self, b'[', b']')
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, ShapeError> {
+        self.tag(b'(')?;
+        self.len(len);
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self, ShapeError> {
+        self.name(b'T', name)?;
+        self.len(len);
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, ShapeError> {
+        self.name(b'e', name)?;
+        self.name(b't', variant)?;
+        self.len(len);
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Unordered<'a>, ShapeError> {
+        Unordered::new(self, b'<', b'>')
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self, ShapeError> {
+        self.name(b'S', name)?;
+        self.len(len);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, ShapeError> {
+        self.name(b'e', name)?;
+        self.name(b'S', variant)?;
+        self.len(len);
+        Ok(self)
+    }
+}
+
+impl ser::SerializeTuple for &mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ShapeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.tag(b')')
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ShapeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.tag(b')')
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ShapeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.tag(b')')
+    }
+}
+
+impl ser::SerializeStruct for &mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ShapeError> {
+        self.name(b'.', key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), ShapeError> {
+        self.name(b'-', key)
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.tag(b'}')
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Shape {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ShapeError> {
+        self.name(b'.', key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), ShapeError> {
+        self.name(b'-', key)
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.tag(b'}')
+    }
+}
+
+/// Sequence or map being fingerprinted: elements (or entries) are
+/// hashed separately and summed, so their order doesn't matter
+struct Unordered<'a> {
+    parent: &'a mut Shape,
+    end: u8,
+    count: u64,
+    sum: u64,
+    item: Shape,
+}
+
+impl<'a> Unordered<'a> {
+    fn new(parent: &'a mut Shape, start: u8, end: u8) -> Result<Self, ShapeError> {
+        parent.tag(start)?;
+        Ok(Unordered {
+            parent,
+            end,
+            count: 0,
+            sum: 0,
+            item: Shape::new(),
+        })
+    }
+
+    fn add_item(&mut self) {
+        self.count += 1;
+        self.sum = self.sum.wrapping_add(self.item.hash);
+        self.item = Shape::new();
+    }
+
+    fn finish(self) -> Result<(), ShapeError> {
+        self.parent.write(&self.count.to_le_bytes());
+        self.parent.write(&self.sum.to_le_bytes());
+        self.parent.tag(self.end)
+    }
+}
+
+impl<'a> ser::SerializeSeq for Unordered<'a> {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ShapeError> {
+        value.serialize(&mut self.item)?;
+        self.add_item();
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeMap for Unordered<'a> {
+    type Ok = ();
+    type Error = ShapeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ShapeError> {
+        key.serialize(&mut self.item)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ShapeError> {
+        value.serialize(&mut self.item)?;
+        self.add_item();
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ShapeError> {
+        self.finish()
+    }
+}
@@ -0,0 +1,128 @@
+extern crate stpl;
+
+use stpl::html::{div, input, AttrValue, PreEscaped, RenderExt};
+
+#[test]
+fn id_override() {
+    assert_eq!(
+        div.id("a").title("t").id("b").render_to_string(),
+        "<div id=\"b\" title=\"t\"></div>"
+    );
+    assert_eq!(
+        div.attr("ID", "a").id("b").render_to_string(),
+        "<div ID=\"b\"></div>"
+    );
+}
+
+#[test]
+fn class_dedup() {
+    assert_eq!(
+        div.class("a b")
+            .class("b c")
+            .attr("class", "a d")
+            .render_to_string(),
+        "<div class=\"a b c d\"></div>"
+    );
+    assert_eq!(
+        div.classes(vec!["x", "y", "x"])
+            .class_if(false, "z")
+            .render_to_string(),
+        "<div class=\"x y\"></div>"
+    );
+    assert_eq!(
+        div.class("a")
+            .attr("class", None::<&str>)
+            .render_to_string(),
+        "<div class=\"a\"></div>"
+    );
+}
+
+#[test]
+fn class_pre_escaped() {
+    assert_eq!(
+        div.attr("class", PreEscaped("a&amp;b")).render_to_string(),
+        "<div class=\"a&amp;b\"></div>"
+    );
+    assert_eq!(
+        div.attr("class", PreEscaped("a&amp;b"))
+            .class("<c>")
+            .class("a&b")
+            .render_to_string(),
+        "<div class=\"a&amp;b &lt;c&gt;\"></div>"
+    );
+    assert_eq!(
+        div.class("&")
+            .attr("class", PreEscaped("&amp; b"))
+            .render_to_string(),
+        "<div class=\"&amp; b\"></div>"
+    );
+}
+
+#[test]
+fn style_merge() {
+    assert_eq!(
+        div.style("color: red;")
+            .style("margin: 0")
+            .render_to_string(),
+        "<div style=\"color: red; margin: 0\"></div>"
+    );
+    assert_eq!(
+        div.style(PreEscaped("content: '&amp;'"))
+            .style("color: red")
+            .render_to_string(),
+        "<div style=\"content: '&amp;'; color: red\"></div>"
+    );
+    assert_eq!(
+        div.style(PreEscaped("content: &quot;x&quot;;"))
+            .style("color: red")
+            .render_to_string(),
+        "<div style=\"content: &quot;x&quot;; color: red\"></div>"
+    );
+    assert_eq!(
+        div.style("content: '&'")
+            .style(PreEscaped("color: red"))
+            .render_to_string(),
+        "<div style=\"content: &#x27;&amp;&#x27;; color: red\"></div>"
+    );
+    assert_eq!(
+        div.style("color: red")
+            .style(None::<&str>)
+            .render_to_string(),
+        "<div style=\"color: red\"></div>"
+    );
+}
+
+#[test]
+fn none_removes() {
+    assert_eq!(
+        div.id("a").title("t").id(None::<&str>).render_to_string(),
+        "<div title=\"t\"></div>"
+    );
+    assert_eq!(
+        div.title(Some("t"))
+            .title(None::<String>)
+            .render_to_string(),
+        "<div></div>"
+    );
+    assert_eq!(
+        input.disabled(true).disabled(false).render_to_string(),
+        "<input>"
+    );
+}
+
+#[test]
+fn get_and_remove_attr() {
+    let tag = div.id("a").attr1("hidden").class("x");
+    assert_eq!(tag.get_attr("id").map(AttrValue::as_str), Some("a"));
+    assert_eq!(tag.get_attr("Id").map(AttrValue::as_str), Some("a"));
+    assert!(tag.get_attr("hidden").is_none());
+    assert!(tag.has_attr("hidden"));
+    assert!(tag.get_attr("title").is_none());
+    assert_eq!(
+        tag.attrs().map(|(k, _)| k).collect::<Vec<_>>(),
+        vec!["id", "hidden", "class"]
+    );
+    let tag = tag.remove_attr("ID").remove_attr("title");
+    assert!(!tag.has_attr("id"));
+    assert_eq!(tag.render_to_string(), "<div hidden class=\"x\"></div>");
+}
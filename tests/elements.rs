@@ -0,0 +1,44 @@
+extern crate stpl;
+
+use stpl::html::*;
+
+#[test]
+fn void_element_has_no_end_tag() {
+    assert_eq!(br.render_to_string(), "<br>");
+    assert_eq!(p.render_to_string(), "<p></p>");
+}
+
+#[test]
+fn escapable_raw_text_is_escaped() {
+    assert_eq!(
+        textarea.with("</textarea>").render_to_string(),
+        "<textarea>&lt;&#x2F;textarea&gt;</textarea>"
+    );
+}
+
+#[test]
+fn data_element_keeps_its_tag_name() {
+    assert_eq!(
+        data_.value("7").with("seven").render_to_string(),
+        "<data value=\"7\">seven</data>"
+    );
+}
+
+#[test]
+fn renamed_elements_dont_shadow_bindings() {
+    let s = "x";
+    let time = 3;
+    let template = ["a", "b"];
+    assert_eq!(
+        div.with((s, time, template.join(","))).render_to_string(),
+        "<div>x3a,b</div>"
+    );
+    assert_eq!(
+        (s_.with("old"), time_.datetime("2020-01-01").with("then")).render_to_string(),
+        "<s>old</s><time datetime=\"2020-01-01\">then</time>"
+    );
+    assert_eq!(
+        style_.with("p { color: red }").render_to_string(),
+        "<style>p { color: red }</style>"
+    );
+}
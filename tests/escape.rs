@@ -0,0 +1,169 @@
+extern crate stpl;
+
+use stpl::html::PreEscaped;
+use stpl::html::{a, comment, css_string, div, img, js_string, p, raw, script, style_, RenderExt};
+use stpl::{Context, ContextRenderer, Render};
+
+fn render_ctx(ctx: Context, s: &str) -> String {
+    let mut out = vec![];
+    s.render(&mut ContextRenderer::new(&mut out, ctx)).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        p.with("<b>\"Tom\" & 'Jerry'</b>").render_to_string(),
+        "<p>&lt;b&gt;&quot;Tom&quot; &amp; &#x27;Jerry&#x27;&lt;&#x2F;b&gt;</p>"
+    );
+    assert_eq!(render_ctx(Context::Text, "a<b>`"), "a&lt;b&gt;&#96;");
+}
+
+#[test]
+fn attr() {
+    assert_eq!(
+        div.title("\"x\" <y> & 'z'").render_to_string(),
+        "<div title=\"&quot;x&quot; &lt;y&gt; &amp; &#x27;z&#x27;\"></div>"
+    );
+    assert_eq!(render_ctx(Context::Attr, "a/b"), "a/b");
+}
+
+#[test]
+fn url_attr() {
+    assert_eq!(
+        a.href("/search?q=a b&x=\"y\"").render_to_string(),
+        "<a href=\"/search?q=a%20b&amp;x=%22y%22\"></a>"
+    );
+    assert_eq!(
+        a.href(" JavaScript:alert(1)").render_to_string(),
+        "<a href=\"about:invalid#stpl-unsafe-url\"></a>"
+    );
+    assert_eq!(
+        render_ctx(Context::UrlAttr, "https://example.com/"),
+        "https://example.com/"
+    );
+}
+
+#[test]
+fn url_attr_data() {
+    assert_eq!(
+        img.src("data:image/png;base64,AAAA").render_to_string(),
+        "<img src=\"data:image/png;base64,AAAA\">"
+    );
+    assert_eq!(
+        img.src("data:image/svg+xml,<svg/>").render_to_string(),
+        "<img src=\"about:invalid#stpl-unsafe-url\">"
+    );
+    assert_eq!(
+        render_ctx(Context::UrlAttr, "data:text/html,<script>"),
+        "about:invalid#stpl-unsafe-url"
+    );
+}
+
+#[test]
+fn script_content() {
+    assert_eq!(
+        script.with("var x = \"a\" < 'b';").render_to_string(),
+        "<script>var x = \"a\" < 'b';</script>"
+    );
+    assert_eq!(
+        script.with("x = '</SCRIPT><!--';").render_to_string(),
+        "<script>x = '<\\/SCRIPT><\\!--';</script>"
+    );
+    // `<` and the rest of the end tag written separately
+    assert_eq!(
+        script.with(("x = '<", "/script>'")).render_to_string(),
+        "<script>x = '<\\/script>'</script>"
+    );
+    assert_eq!(render_ctx(Context::Script, "</style>"), "</style>");
+}
+
+#[test]
+fn script_content_split() {
+    assert_eq!(
+        script.with(("</scr", "ipt>")).render_to_string(),
+        "<script><\\/script></script>"
+    );
+    assert_eq!(
+        script.with(("<", "/", "script>")).render_to_string(),
+        "<script><\\/script></script>"
+    );
+    assert_eq!(
+        script.with(vec!["<!", "-", "-"]).render_to_string(),
+        "<script><\\!--</script>"
+    );
+    // Trusted content in between is not escaped
+    assert_eq!(
+        script
+            .with(("x = '<", raw("/script>"), "';"))
+            .render_to_string(),
+        "<script>x = '</script>';</script>"
+    );
+}
+
+#[test]
+fn style_content() {
+    assert_eq!(
+        style_.with("body { color: red; }").render_to_string(),
+        "<style>body { color: red; }</style>"
+    );
+    assert_eq!(
+        style_
+            .with("a::after { content: '</style>' }")
+            .render_to_string(),
+        "<style>a::after { content: '<\\/style>' }</style>"
+    );
+    assert_eq!(render_ctx(Context::Style, "</script>"), "</script>");
+}
+
+#[test]
+fn style_content_split() {
+    assert_eq!(
+        style_.with(("</sty", "le>")).render_to_string(),
+        "<style><\\/style></style>"
+    );
+    assert_eq!(
+        style_.with(("<", "/", "STYLE>")).render_to_string(),
+        "<style><\\/STYLE></style>"
+    );
+    assert_eq!(
+        style_.with(("<!-", "-")).render_to_string(),
+        "<style><\\!--</style>"
+    );
+}
+
+#[test]
+fn comment_content() {
+    assert_eq!(
+        comment("--><script>").render_to_string(),
+        "<!--&#45;&#45;&gt;&lt;script&gt;-->"
+    );
+    assert_eq!(render_ctx(Context::Comment, "a!b"), "a&#33;b");
+}
+
+#[test]
+fn js_and_css_strings() {
+    assert_eq!(
+        js_string("a\"b</script>\n"),
+        "\"a\\\"b\\u003C/script\\u003E\\n\""
+    );
+    assert_eq!(css_string("a'b"), "'a\\27 b'");
+    assert_eq!(
+        script
+            .with(("var name = ", js_string("</script>"), ";"))
+            .render_to_string(),
+        "<script>var name = \"\\u003C/script\\u003E\";</script>"
+    );
+}
+
+#[test]
+fn raw_content_keeps_attrs_escaped() {
+    assert_eq!(
+        raw(p.title("\"x").with("<b>")).render_to_string(),
+        "<p title=\"&quot;x\"><b></p>"
+    );
+    assert_eq!(
+        PreEscaped(div.title("<y>").with("<i>")).render_to_string(),
+        "<div title=\"&lt;y&gt;\"><i></div>"
+    );
+}
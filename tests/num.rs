@@ -0,0 +1,75 @@
+extern crate stpl;
+
+use stpl::html::{td, RenderExt};
+use stpl::num::{self, Format};
+
+#[test]
+fn grouping() {
+    assert_eq!(num::number(1234567).to_string(), "1,234,567");
+    assert_eq!(num::number(-1234567.5).to_string(), "-1,234,567.5");
+    assert_eq!(num::number(123).to_string(), "123");
+    assert_eq!(num::fixed(1234.5, 2).to_string(), "1,234.50");
+    assert_eq!(num::fixed(1234.567, 2).to_string(), "1,234.57");
+    assert_eq!(num::fixed(12, 1).to_string(), "12.0");
+    assert_eq!(num::number(&1000u64).to_string(), "1,000");
+}
+
+#[test]
+fn custom_separators() {
+    let de = Format::new().thousands_sep(".").decimal_sep(",");
+    assert_eq!(de.currency(1234.5, "€ ").to_string(), "€ 1.234,50");
+    assert_eq!(
+        Format::new().thousands_sep("").number(1234567).to_string(),
+        "1234567"
+    );
+    assert_eq!(
+        td.with(Format::new().thousands_sep("<").number(1000))
+            .render_to_string(),
+        "<td>1&lt;000</td>"
+    );
+}
+
+#[test]
+fn currency_and_percent() {
+    assert_eq!(num::currency(1234.5678, "$").to_string(), "$1,234.57");
+    assert_eq!(num::currency(-5, "$").to_string(), "-$5.00");
+    assert_eq!(num::percent(0.256).to_string(), "26%");
+    assert_eq!(
+        Format::new().precision(1).percent(0.256).to_string(),
+        "25.6%"
+    );
+}
+
+#[test]
+fn negative_zero() {
+    assert_eq!(num::fixed(-0.001, 2).to_string(), "0.00");
+    assert_eq!(num::number(-0.0).to_string(), "0");
+    assert_eq!(num::currency(-0.004, "$").to_string(), "$0.00");
+    assert_eq!(num::compact(-0.01).to_string(), "0");
+}
+
+#[test]
+fn compact() {
+    assert_eq!(num::compact(1234.0).to_string(), "1.2k");
+    assert_eq!(num::compact(1000.0).to_string(), "1k");
+    assert_eq!(num::compact(999.0).to_string(), "999");
+    assert_eq!(num::compact(999_999.0).to_string(), "1M");
+    assert_eq!(num::compact(950.0).to_string(), "950");
+    assert_eq!(num::compact(950_000.0).to_string(), "950k");
+    assert_eq!(num::compact(999.96).to_string(), "1k");
+    assert_eq!(num::compact(999_949.0).to_string(), "999.9k");
+    assert_eq!(
+        Format::new().precision(0).compact(999_500.0).to_string(),
+        "1M"
+    );
+    assert_eq!(num::compact(-2_500_000.0).to_string(), "-2.5M");
+    assert_eq!(num::compact(3e12).to_string(), "3T");
+}
+
+#[test]
+fn not_finite() {
+    assert_eq!(num::number(f64::NAN).to_string(), "NaN");
+    assert_eq!(num::fixed(f64::INFINITY, 2).to_string(), "inf");
+    assert_eq!(num::currency(f64::NEG_INFINITY, "$").to_string(), "-inf");
+    assert_eq!(num::compact(f64::NAN).to_string(), "NaN");
+}
@@ -0,0 +1,25 @@
+extern crate stpl;
+
+use stpl::html::RenderExt;
+use stpl::{Error, Fn, Renderer, TryFn};
+
+#[test]
+fn fn_closure_infers_io_result() {
+    let f = Fn(|r: &mut dyn Renderer| {
+        r.write_raw(b"x")?;
+        Ok(())
+    });
+    assert_eq!(f.render_to_string(), "x");
+}
+
+#[test]
+fn try_fn_closure_reports_error() {
+    let f = TryFn(|r: &mut dyn Renderer| {
+        r.write_raw(b"x")?;
+        Err(Error::custom("no user"))
+    });
+    match f.try_render_to_string() {
+        Err(Error::Custom(e)) => assert_eq!(e.to_string(), "no user"),
+        _ => panic!("expected a custom error"),
+    }
+}
@@ -0,0 +1,76 @@
+extern crate stpl;
+
+use stpl::html::{css_string, div, InvalidStyle, PreEscaped, RenderExt, Style};
+
+#[test]
+fn override_prop() {
+    let style = Style::new().color("red").margin_px(4).prop("COLOR", "blue");
+    assert_eq!(style.to_string(), "color: blue; margin: 4px");
+    assert_eq!(
+        Style::new()
+            .prop_if(false, "color", "red")
+            .width_pct(50)
+            .to_string(),
+        "width: 50%"
+    );
+    assert!(Style::new().is_empty());
+}
+
+#[test]
+fn merge() {
+    let base = Style::new().display("flex").color("red");
+    let extra = Style::new().color("blue").gap_px(2);
+    assert_eq!(
+        base.merge(extra).to_string(),
+        "display: flex; color: blue; gap: 2px"
+    );
+}
+
+#[test]
+fn rejected_values() {
+    let rejected = |name: &str, value: &str| {
+        assert_eq!(
+            Style::new().try_prop(name.to_owned(), value),
+            Err(InvalidStyle {
+                name: name.into(),
+                value: value.into(),
+            })
+        );
+    };
+    rejected("color", "red; background: url(x)");
+    rejected("color", "red}");
+    rejected("color", "");
+    rejected("color", "red /* x */");
+    rejected("content", "'unterminated");
+    rejected("content", "\"x\"");
+    rejected("width", "\\z");
+    rejected("na me", "red");
+    rejected("background", "url(javascript:alert(1))");
+    rejected("width", "expression(alert(1))");
+    // Hex escapes are decoded before the checks
+    rejected("background", "url(\\6a avascript:alert(1))");
+    rejected("background", "url(\\00004A avascript:alert(1))");
+    rejected("width", "\\65 xpression(alert(1))");
+    rejected("width", "expr\\45 ssion(alert(1))");
+
+    assert!(Style::new()
+        .try_prop("font-family", css_string("Comic \"Sans\""))
+        .is_ok());
+    assert!(Style::new().try_prop("--gap", "4px").is_ok());
+}
+
+#[test]
+fn style_attr() {
+    assert_eq!(
+        div.style(Style::new().color("red"))
+            .style(Style::new().margin_px(0))
+            .render_to_string(),
+        "<div style=\"color: red; margin: 0px\"></div>"
+    );
+    assert_eq!(
+        div.style(PreEscaped("color: red;"))
+            .style(Style::new().font_family(css_string("a&b")))
+            .render_to_string(),
+        "<div style=\"color: red; font-family: &#x27;a\\26 b&#x27;\"></div>"
+    );
+}
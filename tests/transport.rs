@@ -0,0 +1,131 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate stpl;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use stpl::transport::{self, Bincode, BuiltIn, Codec, Decoder, Json};
+use stpl::Error;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+enum Kind {
+    Plain,
+    Tagged(u8),
+    Named { name: String },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Data {
+    id: u32,
+    kinds: HashSet<Kind>,
+    maybe: HashSet<Option<u16>>,
+    by_name: HashMap<String, Option<Kind>>,
+}
+
+/// Same layout as `Data`, but with a field renamed
+#[derive(Serialize, Deserialize, Debug)]
+struct Renamed {
+    key: u32,
+    kinds: HashSet<Kind>,
+    maybe: HashSet<Option<u16>>,
+    by_name: HashMap<String, Option<Kind>>,
+}
+
+fn data(n: usize) -> Data {
+    Data {
+        id: 1,
+        kinds: (0..n as u8)
+            .map(|i| match i % 3 {
+                0 => Kind::Plain,
+                1 => Kind::Tagged(i),
+                _ => Kind::Named {
+                    name: i.to_string(),
+                },
+            })
+            .collect(),
+        maybe: (0..n as u16)
+            .map(|i| if i % 2 == 0 { Some(i) } else { None })
+            .collect(),
+        by_name: (0..n)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    if i % 2 == 0 { Some(Kind::Plain) } else { None },
+                )
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn hash_ordered_collections_round_trip() {
+    for n in 0..50 {
+        let data = data(n);
+        // Decoding builds new sets and maps, iterated in a different order
+        let bytes = transport::encode(&Bincode, &data).unwrap();
+        let decoded: Data = transport::decode(&bytes).unwrap();
+        assert_eq!(decoded.kinds, data.kinds);
+        let bytes = transport::encode(&Json, &data).unwrap();
+        let decoded: Data = transport::decode(&bytes).unwrap();
+        assert_eq!(decoded.maybe, data.maybe);
+    }
+}
+
+#[test]
+fn changed_shape_is_detected() {
+    let bytes = transport::encode(&Bincode, &data(10)).unwrap();
+    match transport::decode::<Renamed>(&bytes) {
+        Err(Error::DataShape { .. }) => {}
+        other => panic!("expected a shape error, got {:?}", other),
+    }
+}
+
+#[test]
+fn fingerprint_ignores_values_and_order() {
+    assert_eq!(
+        transport::fingerprint(&vec![Kind::Plain, Kind::Tagged(1)]).unwrap(),
+        transport::fingerprint(&vec![Kind::Tagged(2), Kind::Plain]).unwrap()
+    );
+    assert_ne!(
+        transport::fingerprint(&1u32).unwrap(),
+        transport::fingerprint(&1i32).unwrap()
+    );
+}
+
+#[test]
+fn wrong_codec_is_rejected() {
+    let bytes = transport::encode(&Bincode, &data(3)).unwrap();
+    match transport::decode_with::<_, Data>(&Json, &bytes) {
+        Err(Error::Transport(_)) => {}
+        other => panic!("expected a transport error, got {:?}", other),
+    }
+}
+
+/// JSON under a custom codec id
+struct Custom;
+
+impl Codec for Custom {
+    const ID: u8 = 7;
+
+    fn encode<T: Serialize>(&self, value: &T, out: &mut Vec<u8>) -> Result<(), Error> {
+        Json.encode(value, out)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, Error> {
+        Json.decode(bytes)
+    }
+}
+
+#[test]
+fn custom_codec_is_decoded_by_itself_only() {
+    let bytes = transport::encode(&Custom, &data(3)).unwrap();
+    let decoded: Data = Custom.decode_message(&bytes).unwrap();
+    assert_eq!(decoded.id, 1);
+    match BuiltIn.decode_message::<Data>(&bytes) {
+        Err(Error::Transport(_)) => {}
+        other => panic!("expected a transport error, got {:?}", other),
+    }
+}